@@ -18,3 +18,134 @@ finished-download = Finished downloading { $title }
 missing-format = Invalid format
 browse = Browse
 folder = Download Folder
+settings = Settings
+back = Back
+clipboard-watch = Offer to download copied links
+clipboard-found = Copied link ready to download
+dismiss = Dismiss
+domain-allow = Only watch these domains
+domain-deny = Never watch these domains
+retry = Retry
+setup-checking = Setting up…
+setup-yt-dlp = Installing yt-dlp…
+setup-ffmpeg = Installing ffmpeg…
+setup-update = Updating yt-dlp…
+yt-dlp-version = yt-dlp { $version }
+yt-dlp-latest = Latest: { $version }
+update-yt-dlp = Update yt-dlp
+update-interval = Check for updates
+never = Never
+daily = Daily
+weekly = Weekly
+binaries = yt-dlp and ffmpeg
+binaries-bundled = Bundled
+binaries-system = System
+binaries-custom = Custom paths
+apply = Apply
+setup-import = Importing yt-dlp and ffmpeg…
+import-path = Provision dependencies from a local folder or archive
+import = Import
+import-unverified = Imported { $binaries } without checksums to verify them
+quarantined = { $file } changed since it was installed, so it was moved aside and installed again
+proxy = Proxy
+rate-limit = Bandwidth limit
+source-address = Source address
+ip-version = IP version
+ip-any = Any
+timeout = Timeout (seconds)
+retries = Retries
+default = Default
+cookies-file = Cookies file (cookies.txt)
+use-netrc = Use logins from ~/.netrc
+logins = Site logins, kept in the keyring
+domain = Domain
+username = Username
+password = Password
+save = Save
+remove = Remove
+preset = Preset
+no-preset = None
+presets = Presets
+preset-name = Preset name
+extra-args = Extra yt-dlp arguments
+save-preset = Save current selection as preset
+already-downloaded = { $title } was already downloaded
+download-anyway = Download anyway
+archive = Download archive
+export-archive = Export…
+subscriptions = Subscriptions
+check-now = Check now
+feed-interval = Check subscriptions
+feed-url = Channel or playlist URL
+feed-existing = Also download current items
+subscribe = Subscribe
+feeds-added = { $total ->
+    [one] Added {$total} new download from subscriptions
+    *[other] Added {$total} new downloads from subscriptions
+}
+start-at = Start at
+now = Now
+starts-at = Starts { $time }
+cancel = Cancel
+download-window = Only download between
+on-metered = On metered connections
+on-battery = On battery
+battery-below = Only below charge (%)
+any-charge = Any charge
+policy-ignore = Download anyway
+policy-defer = Wait to start
+policy-pause = Stop and restart later
+held-metered = Waiting for an unmetered connection
+held-battery = Waiting for AC power
+clip = Only download
+clip-start = Start
+clip-end = End
+load-chapters = Load chapters
+chapter = Chapter
+no-chapters = No chapters found
+split-chapters = Split by chapters
+chapter-template = Chapter file names
+chapter-playlist = Write a playlist of the chapters
+sponsorblock = SponsorBlock segments
+category-sponsor = Sponsor
+category-selfpromo = Self promotion
+category-interaction = Interaction reminder
+category-intro = Intro
+category-outro = Endcards and credits
+category-preview = Preview and recap
+category-filler = Filler tangent
+category-music-offtopic = Non-music section
+segment-keep = Keep
+segment-mark = Mark as chapter
+segment-remove = Remove
+batch = One URL per line
+batch-placeholder = https://…
+import-list = Import list…
+url-lists = URL lists
+invalid-line = Line { $line } is not a web URL: { $text }
+duplicates-skipped = { $total ->
+    [one] Skipped {$total} duplicate URL
+    *[other] Skipped {$total} duplicate URLs
+}
+url-empty = Enter a URL
+url-invalid = Not a valid URL
+url-scheme = Only http and https links can be downloaded
+url-checking = Checking URL…
+url-supported = Supported by { $extractor }
+url-unsupported = No yt-dlp extractor supports this URL
+folder-rules = Folder rules
+folder-rule = { $kind }, { $field } { $pattern } → { $folder }
+rule-any-type = Any type
+rule-any-value = (any)
+rule-domain = Domain
+rule-uploader = Uploader
+rule-playlist = Playlist
+rule-pattern = Value to match, empty for any
+add-rule = Add rule
+folder-missing = The folder doesn't exist
+folder-not-folder = This is a file, not a folder
+folder-read-only = The folder can't be written to
+create-folder = Create folder
+no-space = Not enough space for { $title }: { $needed } needed, { $free } free
+url-generic = No dedicated extractor, yt-dlp will look for media in the page
+url-unknown = Couldn't check the URL, yt-dlp will report any problem
@@ -18,3 +18,134 @@ finished-download = Donwload completo: { $title }
 missing-format = Formato inválido
 browse = Browse
 folder = Pasta de Download
+settings = Configurações
+back = Voltar
+clipboard-watch = Oferecer download de links copiados
+clipboard-found = Link copiado pronto para baixar
+dismiss = Dispensar
+domain-allow = Observar apenas estes domínios
+domain-deny = Nunca observar estes domínios
+retry = Tentar novamente
+setup-checking = Configurando…
+setup-yt-dlp = Instalando o yt-dlp…
+setup-ffmpeg = Instalando o ffmpeg…
+setup-update = Atualizando o yt-dlp…
+yt-dlp-version = yt-dlp { $version }
+yt-dlp-latest = Mais recente: { $version }
+update-yt-dlp = Atualizar o yt-dlp
+update-interval = Verificar atualizações
+never = Nunca
+daily = Diariamente
+weekly = Semanalmente
+binaries = yt-dlp e ffmpeg
+binaries-bundled = Embutidos
+binaries-system = Do sistema
+binaries-custom = Caminhos personalizados
+apply = Aplicar
+setup-import = Importando o yt-dlp e o ffmpeg…
+import-path = Obter dependências de uma pasta ou arquivo local
+import = Importar
+import-unverified = { $binaries } importado sem somas de verificação para conferir
+quarantined = { $file } foi alterado desde a instalação, então foi movido e instalado novamente
+proxy = Proxy
+rate-limit = Limite de banda
+source-address = Endereço de origem
+ip-version = Versão do IP
+ip-any = Qualquer
+timeout = Tempo limite (segundos)
+retries = Tentativas
+default = Padrão
+cookies-file = Arquivo de cookies (cookies.txt)
+use-netrc = Usar logins do ~/.netrc
+logins = Logins de sites, guardados no chaveiro
+domain = Domínio
+username = Usuário
+password = Senha
+save = Salvar
+remove = Remover
+preset = Predefinição
+no-preset = Nenhuma
+presets = Predefinições
+preset-name = Nome da predefinição
+extra-args = Argumentos extras do yt-dlp
+save-preset = Salvar seleção atual como predefinição
+already-downloaded = { $title } já foi baixado
+download-anyway = Baixar mesmo assim
+archive = Arquivo de downloads
+export-archive = Exportar…
+subscriptions = Inscrições
+check-now = Verificar agora
+feed-interval = Verificar inscrições
+feed-url = URL do canal ou playlist
+feed-existing = Baixar também os itens atuais
+subscribe = Inscrever-se
+feeds-added = { $total ->
+    [one] {$total} novo download adicionado das inscrições
+    *[other] {$total} novos downloads adicionados das inscrições
+}
+start-at = Iniciar às
+now = Agora
+starts-at = Começa { $time }
+cancel = Cancelar
+download-window = Baixar somente entre
+on-metered = Em conexões limitadas
+on-battery = Na bateria
+battery-below = Somente abaixo da carga (%)
+any-charge = Qualquer carga
+policy-ignore = Baixar mesmo assim
+policy-defer = Esperar para iniciar
+policy-pause = Parar e recomeçar depois
+held-metered = Aguardando uma conexão ilimitada
+held-battery = Aguardando a energia da tomada
+clip = Baixar somente
+clip-start = Início
+clip-end = Fim
+load-chapters = Carregar capítulos
+chapter = Capítulo
+no-chapters = Nenhum capítulo encontrado
+split-chapters = Dividir por capítulos
+chapter-template = Nomes dos arquivos dos capítulos
+chapter-playlist = Criar uma playlist dos capítulos
+sponsorblock = Segmentos do SponsorBlock
+category-sponsor = Patrocínio
+category-selfpromo = Autopromoção
+category-interaction = Lembrete de interação
+category-intro = Introdução
+category-outro = Telas finais e créditos
+category-preview = Prévia e recapitulação
+category-filler = Tangente
+category-music-offtopic = Trecho sem música
+segment-keep = Manter
+segment-mark = Marcar como capítulo
+segment-remove = Remover
+batch = Uma URL por linha
+batch-placeholder = https://…
+import-list = Importar lista…
+url-lists = Listas de URLs
+invalid-line = A linha { $line } não é uma URL da web: { $text }
+duplicates-skipped = { $total ->
+    [one] {$total} URL duplicada ignorada
+    *[other] {$total} URLs duplicadas ignoradas
+}
+url-empty = Digite uma URL
+url-invalid = URL inválida
+url-scheme = Somente links http e https podem ser baixados
+url-checking = Verificando URL…
+url-supported = Suportado por { $extractor }
+url-unsupported = Nenhum extrator do yt-dlp suporta esta URL
+folder-rules = Regras de pastas
+folder-rule = { $kind }, { $field } { $pattern } → { $folder }
+rule-any-type = Qualquer tipo
+rule-any-value = (qualquer)
+rule-domain = Domínio
+rule-uploader = Autor
+rule-playlist = Playlist
+rule-pattern = Valor a corresponder, vazio para qualquer
+add-rule = Adicionar regra
+folder-missing = A pasta não existe
+folder-not-folder = Isto é um arquivo, não uma pasta
+folder-read-only = Não é possível gravar na pasta
+create-folder = Criar pasta
+no-space = Espaço insuficiente para { $title }: { $needed } necessários, { $free } livres
+url-generic = Sem extrator dedicado, o yt-dlp vai procurar mídia na página
+url-unknown = Não foi possível verificar a URL, o yt-dlp vai relatar qualquer problema
@@ -18,3 +18,134 @@ finished-download = Slutförd nedladdning { $title }
 missing-format = Ogiltigt format
 browse = Bläddra
 folder = Nedladdningsmapp
+settings = Inställningar
+back = Tillbaka
+clipboard-watch = Erbjud nedladdning av kopierade länkar
+clipboard-found = Kopierad länk redo att laddas ned
+dismiss = Avfärda
+domain-allow = Bevaka endast dessa domäner
+domain-deny = Bevaka aldrig dessa domäner
+retry = Försök igen
+setup-checking = Förbereder…
+setup-yt-dlp = Installerar yt-dlp…
+setup-ffmpeg = Installerar ffmpeg…
+setup-update = Uppdaterar yt-dlp…
+yt-dlp-version = yt-dlp { $version }
+yt-dlp-latest = Senaste: { $version }
+update-yt-dlp = Uppdatera yt-dlp
+update-interval = Sök efter uppdateringar
+never = Aldrig
+daily = Dagligen
+weekly = Varje vecka
+binaries = yt-dlp och ffmpeg
+binaries-bundled = Medföljande
+binaries-system = Systemets
+binaries-custom = Egna sökvägar
+apply = Verkställ
+setup-import = Importerar yt-dlp och ffmpeg…
+import-path = Hämta beroenden från en lokal mapp eller ett arkiv
+import = Importera
+import-unverified = Importerade { $binaries } utan kontrollsummor att verifiera mot
+quarantined = { $file } har ändrats sedan installationen, så den flyttades undan och installerades igen
+proxy = Proxy
+rate-limit = Bandbreddsgräns
+source-address = Källadress
+ip-version = IP-version
+ip-any = Valfri
+timeout = Tidsgräns (sekunder)
+retries = Försök
+default = Standard
+cookies-file = Kakfil (cookies.txt)
+use-netrc = Använd inloggningar från ~/.netrc
+logins = Inloggningar för webbplatser, sparade i nyckelringen
+domain = Domän
+username = Användarnamn
+password = Lösenord
+save = Spara
+remove = Ta bort
+preset = Förinställning
+no-preset = Ingen
+presets = Förinställningar
+preset-name = Namn på förinställning
+extra-args = Extra argument till yt-dlp
+save-preset = Spara nuvarande val som förinställning
+already-downloaded = { $title } har redan laddats ner
+download-anyway = Ladda ner ändå
+archive = Nedladdningsarkiv
+export-archive = Exportera…
+subscriptions = Prenumerationer
+check-now = Kontrollera nu
+feed-interval = Kontrollera prenumerationer
+feed-url = Kanal- eller spellistans URL
+feed-existing = Ladda även ner nuvarande objekt
+subscribe = Prenumerera
+feeds-added = { $total ->
+    [one] Lade till {$total} ny nedladdning från prenumerationer
+    *[other] Lade till {$total} nya nedladdningar från prenumerationer
+}
+start-at = Starta klockan
+now = Nu
+starts-at = Startar { $time }
+cancel = Avbryt
+download-window = Ladda bara ner mellan
+on-metered = På uppkopplingar med datapriser
+on-battery = På batteri
+battery-below = Bara under laddning (%)
+any-charge = Vilken laddning som helst
+policy-ignore = Ladda ner ändå
+policy-defer = Vänta med att starta
+policy-pause = Stoppa och börja om senare
+held-metered = Väntar på en uppkoppling utan datapriser
+held-battery = Väntar på nätström
+clip = Ladda bara ner
+clip-start = Start
+clip-end = Slut
+load-chapters = Läs in kapitel
+chapter = Kapitel
+no-chapters = Inga kapitel hittades
+split-chapters = Dela upp efter kapitel
+chapter-template = Filnamn för kapitel
+chapter-playlist = Skriv en spellista över kapitlen
+sponsorblock = SponsorBlock-segment
+category-sponsor = Sponsor
+category-selfpromo = Egenreklam
+category-interaction = Påminnelse om interaktion
+category-intro = Intro
+category-outro = Slutskärmar och eftertexter
+category-preview = Förhandsvisning och sammanfattning
+category-filler = Utfyllnad
+category-music-offtopic = Avsnitt utan musik
+segment-keep = Behåll
+segment-mark = Markera som kapitel
+segment-remove = Ta bort
+batch = En URL per rad
+batch-placeholder = https://…
+import-list = Importera lista…
+url-lists = URL-listor
+invalid-line = Rad { $line } är inte en webbadress: { $text }
+duplicates-skipped = { $total ->
+    [one] Hoppade över {$total} dubblett
+    *[other] Hoppade över {$total} dubbletter
+}
+url-empty = Ange en URL
+url-invalid = Ogiltig URL
+url-scheme = Endast http- och https-länkar kan laddas ner
+url-checking = Kontrollerar URL…
+url-supported = Stöds av { $extractor }
+url-unsupported = Ingen yt-dlp-extraktor stöder denna URL
+folder-rules = Mappregler
+folder-rule = { $kind }, { $field } { $pattern } → { $folder }
+rule-any-type = Alla typer
+rule-any-value = (alla)
+rule-domain = Domän
+rule-uploader = Uppladdare
+rule-playlist = Spellista
+rule-pattern = Värde att matcha, tomt för alla
+add-rule = Lägg till regel
+folder-missing = Mappen finns inte
+folder-not-folder = Det här är en fil, inte en mapp
+folder-read-only = Det går inte att skriva till mappen
+create-folder = Skapa mapp
+no-space = Inte tillräckligt med utrymme för { $title }: { $needed } behövs, { $free } ledigt
+url-generic = Ingen särskild extraktor, yt-dlp letar efter media på sidan
+url-unknown = Kunde inte kontrollera URL:en, yt-dlp rapporterar eventuella problem
@@ -1,24 +1,51 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use cosmic::app::{Core, Task};
 use cosmic::applet::padded_control;
+use cosmic::cosmic_config;
 use cosmic::cosmic_theme::Spacing;
+use cosmic::iced::futures::SinkExt;
 use cosmic::iced::platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup};
 use cosmic::iced::window::Id;
-use cosmic::iced::{Alignment, Length, Limits};
-use cosmic::iced_widget::{button, column, pick_list, row};
+use cosmic::iced::{Alignment, Length, Limits, Subscription, stream, time};
+use cosmic::iced_widget::{button, column, pick_list, row, text_editor, toggler};
+use cosmic::widget::dnd_destination::DndDestination;
 use cosmic::widget::segmented_button::{Entity, SingleSelectModel};
 use cosmic::widget::text::body;
-use cosmic::widget::{divider, segmented_control, text_input};
+use cosmic::widget::{divider, scrollable, segmented_control, text_input};
 use cosmic::{Action, Application, Apply, Element};
 
-use ashpd::desktop::file_chooser::SelectedFiles;
+use ashpd::desktop::file_chooser::{FileFilter, SelectedFiles};
+use chrono::Local;
 use notify_rust::Notification;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::BroadcastStream;
 
-use crate::formats::{AudioCodec, AudioQuality, VideoCodec, VideoQuality};
-use crate::{fetcher, fl, fl_str};
+use crate::auth::{self, Auth, Credential, Login};
+use crate::batch;
+use crate::clip::{self, Chapter, Clip};
+use crate::clipboard::{self, DroppedUrls};
+use crate::config::{self, BinarySource, Config, UpdateInterval};
+use crate::dbus;
+use crate::download::{self, DownloadService, YtdlpBackend};
+use crate::fetcher::{Binaries, SetupStep, Support};
+use crate::folders::{self, Problem};
+use crate::formats::{AudioCodec, AudioQuality, DownloadOptions, VideoCodec, VideoQuality};
+use crate::jobs::JobState;
+use crate::network::{IpVersion, Network};
+use crate::power::{self, Conditions, Policy, PowerPolicy};
+use crate::presets::{self, Preset};
+use crate::rules::{self, Field, FolderRule};
+use crate::schedule::{self, ScheduledJob, Window};
+use crate::split::{self, Split};
+use crate::sponsorblock::{self, Category, SponsorBlock};
+use crate::subscriptions::Feed;
+use crate::urls::{self, Invalid};
+use crate::{fetcher, fl, fl_str, notifications};
 
 #[derive(Default)]
 pub struct Ytdlp {
@@ -26,41 +53,292 @@ pub struct Ytdlp {
 
     download_type: SingleSelectModel,
     video_entity: Entity,
+    audio_entity: Entity,
 
     video_folder: String,
     audio_folder: String,
+    /// Why the selected type's folder can't be downloaded to.
+    folder_problem: Option<Problem>,
+    /// Folder that couldn't be created, and why.
+    folder_error: Option<(PathBuf, String)>,
     url: String,
+    url_status: UrlStatus,
+    /// Bumped on every edit of the URL, so only the last check counts.
+    url_check: u32,
+    /// What came of checking each URL, by the URL as typed, so none is looked up twice.
+    url_checks: HashMap<String, (String, Support)>,
+    /// Whether the popup takes a list of URLs, one per line.
+    batch_mode: bool,
+    batch: text_editor::Content,
+    batch_errors: Vec<String>,
+    /// Time of day to start the next download at, empty for now.
+    start_at: String,
+    clip_start: String,
+    clip_end: String,
+    /// Chapters of the entered URL, once loaded.
+    chapters: Vec<Chapter>,
+    chapter: Option<Chapter>,
+    loading_chapters: bool,
+    chapters_error: Option<String>,
 
     video_quality: VideoQuality,
     audio_quality: AudioQuality,
     video_codec: VideoCodec,
     audio_codec: AudioCodec,
 
-    lib_dir: PathBuf,
+    config_handler: Option<cosmic_config::Config>,
+    config: Config,
+    show_settings: bool,
+    /// Selected preset, empty for none.
+    preset: String,
+    preset_draft: Preset,
+    rule_draft: FolderRule,
+    credential: Credential,
+    credential_password: String,
+    auth_error: Option<String>,
+
+    clipboard_last: String,
+    clipboard_url: Option<String>,
+
+    setup: Option<SetupStep>,
+    setup_error: Option<String>,
+    binaries: Binaries,
+    downloads: Option<DownloadService>,
+
+    ytdlp_version: Option<String>,
+    latest_version: Option<String>,
+    updating: bool,
+    update_error: Option<String>,
+    archive_error: Option<String>,
+    feed_draft: Feed,
+    feed_existing: bool,
+    adding_feed: bool,
+    checking_feeds: bool,
+    feed_error: Option<String>,
+    /// Jobs waiting for their start time or the download window, by job id.
+    scheduled: Vec<(u32, ScheduledJob)>,
+    window_start: String,
+    window_end: String,
+    conditions: Conditions,
+    /// Jobs started by the applet, to restart them after pausing, by job id.
+    running: Vec<(u32, ScheduledJob)>,
+    /// Jobs skipped as already downloaded, kept until the user decides whether to
+    /// download them anyway, by job id.
+    skipped: Vec<(u32, ScheduledJob)>,
+
     popup: Option<Id>,
-    downloading: u32,
+}
+
+/// What is known about the entered URL.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+enum UrlStatus {
+    #[default]
+    Unchecked,
+    Invalid(Invalid),
+    Checking,
+    /// Handled by the named yt-dlp extractor.
+    Supported(String),
+    /// Only yt-dlp's generic extractor applies; it may still find media.
+    Generic,
+    Unsupported,
+    /// The check failed or timed out.
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub enum SetupEvent {
+    Step(SetupStep),
+    Done(Result<Binaries, String>),
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     TogglePopup,
     PopupClosed(Id),
+    ToggleSettings,
+    UpdateConfig(Config),
+    Url(UrlMessage),
+    Clip(ClipMessage),
+    Downloads(DownloadsMessage),
+    Jobs(JobsMessage),
+    Folder(FolderMessage),
+    Feeds(FeedsMessage),
+    Schedule(ScheduleMessage),
+    Setup(SetupMessage),
+    Ytdlp(YtdlpMessage),
+    Network(NetworkMessage),
+    Presets(PresetsMessage),
+    Clipboard(ClipboardMessage),
+}
+
+/// URL entry and batch lists.
+#[derive(Debug, Clone)]
+pub enum UrlMessage {
     EnterURL(String),
-    SelectFolder,
-    ProcessSelectFolder(String),
+    CheckUrl(u32),
+    /// The check, the URL as typed, where it leads and what yt-dlp makes of it.
+    UrlChecked(u32, String, String, Support),
+    BatchMode(bool),
+    EditBatch(text_editor::Action),
+    ImportList,
+    ListImported(Option<Result<String, String>>),
+}
+
+/// Clips, chapters and SponsorBlock.
+#[derive(Debug, Clone)]
+pub enum ClipMessage {
+    ClipStart(String),
+    ClipEnd(String),
+    LoadChapters,
+    ChaptersLoaded(Result<Vec<Chapter>, String>),
+    Chapter(Chapter),
+    SplitChapters(bool),
+    ChapterTemplate(String),
+    ChapterPlaylist(bool),
+    SponsorBlockApi(String),
+    SegmentAction(Category, sponsorblock::Action),
+}
+
+/// Download options and queueing downloads.
+#[derive(Debug, Clone)]
+pub enum DownloadsMessage {
     ChangeType(Entity),
+    DownloadAnyway(u32),
+    DismissSkipped(u32),
+    StartAt(String),
+    Preset(String),
     VideoQuality(VideoQuality),
     AudioQuality(AudioQuality),
     VideoCodec(VideoCodec),
     AudioCodec(AudioCodec),
     Download,
-    Finished,
+    Dbus(dbus::Request),
+}
+
+/// Running jobs and the download archive.
+#[derive(Debug, Clone)]
+pub enum JobsMessage {
+    ExportArchive,
+    ArchiveExported(Option<String>),
+    CancelJob(u32),
+    Job(download::Event),
+}
+
+/// The download folder.
+#[derive(Debug, Clone)]
+pub enum FolderMessage {
+    SelectFolder,
+    ProcessSelectFolder(String),
+    EnterFolder(String),
+    CreateFolder,
+    FolderCreated(Result<(), (PathBuf, String)>),
+}
+
+/// Feed subscriptions.
+#[derive(Debug, Clone)]
+pub enum FeedsMessage {
+    FeedUrl(String),
+    FeedPreset(String),
+    FeedFolder(String),
+    FeedExisting(bool),
+    AddFeed,
+    FeedAdded(Result<Feed, String>),
+    RemoveFeed(usize),
+    FeedInterval(UpdateInterval),
+    PollFeeds,
+    CheckFeeds,
+    FeedsChecked(Vec<(String, Result<Vec<String>, String>)>),
+}
+
+/// Scheduled downloads and the power policy.
+#[derive(Debug, Clone)]
+pub enum ScheduleMessage {
+    RunScheduled,
+    WindowEnabled(bool),
+    WindowStart(String),
+    WindowEnd(String),
+    Conditions(Conditions),
+    MeteredPolicy(Policy),
+    BatteryPolicy(Policy),
+    BatteryBelow(String),
+}
+
+/// Setting up, importing and choosing the yt-dlp and ffmpeg binaries.
+#[derive(Debug, Clone)]
+pub enum SetupMessage {
+    Event(SetupEvent),
+    RetrySetup,
+    BinarySource(BinarySource),
+    CustomYtdlp(String),
+    CustomFfmpeg(String),
+    ImportPath(String),
+    ImportDeps,
+}
+
+/// The yt-dlp version and its updates.
+#[derive(Debug, Clone)]
+pub enum YtdlpMessage {
+    YtdlpVersion(Option<String>),
+    CheckUpdate,
+    UpdateChecked(Option<String>, bool),
+    UpdateYtdlp,
+    Updated(Result<String, String>),
+    UpdateInterval(UpdateInterval),
+}
+
+/// Network settings and logins.
+#[derive(Debug, Clone)]
+pub enum NetworkMessage {
+    Proxy(String),
+    RateLimit(String),
+    SourceAddress(String),
+    IpVersion(IpVersion),
+    NetworkTimeout(String),
+    Retries(String),
+    CookiesFile(String),
+    Netrc(bool),
+    CredentialDomain(String),
+    CredentialUsername(String),
+    CredentialPassword(String),
+    SaveCredential,
+    CredentialSaved(Result<Credential, String>),
+    RemoveCredential(usize),
+}
+
+/// Presets and folder rules.
+#[derive(Debug, Clone)]
+pub enum PresetsMessage {
+    PresetName(String),
+    PresetTemplate(String),
+    PresetArgs(String),
+    SavePreset,
+    RemovePreset(usize),
+    RuleKind(rules::Kind),
+    RuleField(Field),
+    RulePattern(String),
+    RuleFolder(String),
+    AddRule,
+    RemoveRule(usize),
+}
+
+/// Clipboard watching and dropped URLs.
+#[derive(Debug, Clone)]
+pub enum ClipboardMessage {
+    ClipboardWatch(bool),
+    DomainAllow(String),
+    DomainDeny(String),
+    PollClipboard,
+    ClipboardChanged(Option<String>),
+    ClipboardChecked(String, Support),
+    DownloadClipboard,
+    DismissClipboard,
+    Dropped(Vec<String>),
 }
 
 impl Application for Ytdlp {
     type Executor = cosmic::executor::Default;
 
-    type Flags = PathBuf;
+    type Flags = ();
 
     type Message = Message;
 
@@ -74,36 +352,36 @@ impl Application for Ytdlp {
         &mut self.core
     }
 
-    fn init(core: Core, flags: Self::Flags) -> (Self, Task<Self::Message>) {
+    fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
         let mut download_type = SingleSelectModel::default();
         let video_entity = download_type.insert().text(fl!("video")).id();
-        download_type.insert().text(fl!("audio"));
+        let audio_entity = download_type.insert().text(fl!("audio")).id();
         download_type.activate(video_entity);
 
-        let video_folder = xdg_user::videos()
-            .ok()
-            .flatten()
-            .map_or(String::from("~/Videos"), |path| {
-                String::from(path.to_string_lossy())
-            });
-        let audio_folder = xdg_user::music()
-            .ok()
-            .flatten()
-            .map_or(String::from("~/Music"), |path| {
-                String::from(path.to_string_lossy())
-            });
+        let video_folder = download::default_folder(true);
+        let audio_folder = download::default_folder(false);
+
+        let (config_handler, config) = Config::load(Self::APP_ID);
 
-        let app = Ytdlp {
+        let mut app = Ytdlp {
             core,
             download_type,
             video_entity,
+            audio_entity,
             video_folder,
             audio_folder,
-            lib_dir: flags,
+            config_handler,
+            config,
+            setup: Some(SetupStep::Checking),
             ..Default::default()
         };
+        app.select_preset(app.config.last_preset.clone());
+        app.window_start = schedule::format_time(app.config.window.start);
+        app.window_end = schedule::format_time(app.config.window.end);
 
-        (app, Task::none())
+        // Install dependencies in the background, so the panel icon shows up right away
+        let setup = Self::setup(app.config.clone());
+        (app, setup)
     }
 
     fn on_close_requested(&self, id: Id) -> Option<Message> {
@@ -111,14 +389,28 @@ impl Application for Ytdlp {
     }
 
     fn view(&self) -> Element<Self::Message> {
+        // Badge the panel icon while a copied link is waiting
+        let icon = if self.clipboard_url.is_some() {
+            "folder-download-symbolic"
+        } else {
+            "multimedia-video-player-symbolic"
+        };
         self.core
             .applet
-            .icon_button("multimedia-video-player-symbolic")
+            .icon_button(icon)
             .on_press(Message::TogglePopup)
             .into()
     }
 
     fn view_window(&self, _id: Id) -> Element<Self::Message> {
+        if self.show_settings {
+            return self
+                .core
+                .applet
+                .popup_container(self.view_settings())
+                .into();
+        }
+
         let video_selected = self.video_entity == self.download_type.active();
         let pad = self.core.applet.suggested_padding(true);
         let Spacing {
@@ -126,12 +418,12 @@ impl Application for Ytdlp {
         } = cosmic::theme::active().cosmic().spacing;
 
         let content_list = column![
-            text_input(fl!("url"), &self.url)
-                .on_input(Message::EnterURL)
-                .apply(padded_control)
-                .width(Length::Fill),
+            self.view_setup(),
+            self.view_clipboard(),
+            self.view_url(),
+            self.view_preset(),
             segmented_control::horizontal(&self.download_type)
-                .on_activate(Message::ChangeType)
+                .on_activate(|id| Message::Downloads(DownloadsMessage::ChangeType(id)))
                 .apply(padded_control)
                 .width(Length::Fill),
             if video_selected {
@@ -142,7 +434,7 @@ impl Application for Ytdlp {
             padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
             row![
                 body(fl!("folder")).width(Length::Fill),
-                button(body(fl!("browse"))).on_press(Message::SelectFolder)
+                button(body(fl!("browse"))).on_press(Message::Folder(FolderMessage::SelectFolder))
             ]
             .align_y(Alignment::Center)
             .spacing(pad)
@@ -155,30 +447,118 @@ impl Application for Ytdlp {
                     self.audio_folder.clone()
                 }
             )
-            .on_focus(Message::SelectFolder)
-            .on_input(Message::ProcessSelectFolder)
+            .on_focus(Message::Folder(FolderMessage::SelectFolder))
+            .on_input(|folder| Message::Folder(FolderMessage::EnterFolder(folder)))
             .apply(padded_control),
+            self.view_folder_problem(),
             padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
             row![
-                body(fl!("downloading", total = self.downloading)).width(Length::Fill),
-                button(body(fl!("download"))).on_press(Message::Download),
+                body(fl!("start-at")).width(Length::FillPortion(1)),
+                text_input(fl!("now"), &self.start_at)
+                    .on_input(|start_at| Message::Downloads(DownloadsMessage::StartAt(start_at)))
+                    .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .apply(padded_control),
+            row![
+                body(fl!(
+                    "downloading",
+                    total = self
+                        .downloads
+                        .as_ref()
+                        .map_or(0, |d| d.jobs().active())
+                        .saturating_sub(self.scheduled.len())
+                ))
+                .width(Length::Fill),
+                button(body(fl!("download"))).on_press_maybe(
+                    (self.downloads.is_some()
+                        && self.folder_problem.is_none()
+                        && self.start_valid()
+                        && self.input_valid())
+                    .then_some(Message::Downloads(DownloadsMessage::Download))
+                ),
             ]
             .align_y(Alignment::Center)
             .spacing(pad)
-            .apply(padded_control)
+            .apply(padded_control),
+            self.view_scheduled(),
+            button(body(fl!("settings")))
+                .on_press(Message::ToggleSettings)
+                .apply(padded_control),
         ]
         .padding(pad);
+        // Links dragged from a browser are queued right away
+        let content_list =
+            DndDestination::for_data(content_list, |urls: Option<DroppedUrls>, _| {
+                Message::Clipboard(ClipboardMessage::Dropped(
+                    urls.map(|urls| urls.0).unwrap_or_default(),
+                ))
+            });
 
         self.core.applet.popup_container(content_list).into()
     }
 
-    #[allow(clippy::too_many_lines)]
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let mut subscriptions = vec![
+            self.core
+                .watch_config::<Config>(Self::APP_ID)
+                .map(|update| Message::UpdateConfig(update.config)),
+        ];
+        if let Some(downloads) = &self.downloads {
+            subscriptions.push(
+                Subscription::run_with_id(
+                    "download-events",
+                    BroadcastStream::new(downloads.subscribe()).filter_map(Result::ok),
+                )
+                .map(|event| Message::Jobs(JobsMessage::Job(event))),
+            );
+            subscriptions.push(
+                dbus::subscription(downloads.clone())
+                    .map(|request| Message::Downloads(DownloadsMessage::Dbus(request))),
+            );
+            if self.config.update_interval != UpdateInterval::Never {
+                subscriptions.push(
+                    time::every(Duration::from_secs(60 * 60))
+                        .map(|_| Message::Ytdlp(YtdlpMessage::CheckUpdate)),
+                );
+            }
+            if !self.scheduled.is_empty() {
+                subscriptions.push(
+                    time::every(Duration::from_secs(60))
+                        .map(|_| Message::Schedule(ScheduleMessage::RunScheduled)),
+                );
+            }
+            if self.config.power.is_set() {
+                subscriptions.push(
+                    power::subscription().map(|conditions| {
+                        Message::Schedule(ScheduleMessage::Conditions(conditions))
+                    }),
+                );
+            }
+            if self.config.feed_interval != UpdateInterval::Never && !self.config.feeds.is_empty() {
+                subscriptions.push(
+                    time::every(Duration::from_secs(15 * 60))
+                        .map(|_| Message::Feeds(FeedsMessage::PollFeeds)),
+                );
+            }
+        }
+        if self.config.clipboard_watch {
+            subscriptions.push(
+                time::every(Duration::from_secs(2))
+                    .map(|_| Message::Clipboard(ClipboardMessage::PollClipboard)),
+            );
+        }
+        Subscription::batch(subscriptions)
+    }
+
     fn update(&mut self, message: Self::Message) -> Task<Self::Message> {
         match message {
             Message::TogglePopup => {
                 return if let Some(p) = self.popup.take() {
                     destroy_popup(p)
                 } else {
+                    // The folder may have been removed since it was last checked
+                    self.check_folder();
                     let new_id = Id::unique();
                     self.popup.replace(new_id);
                     let mut popup_settings = self.core.applet.get_popup_settings(
@@ -201,13 +581,354 @@ impl Application for Ytdlp {
                     self.popup = None;
                 }
             }
-            Message::EnterURL(url) => self.url = url,
-            Message::ChangeType(id) => self.download_type.activate(id),
-            Message::VideoQuality(video_resolution) => self.video_quality = video_resolution,
-            Message::AudioQuality(audio_quality) => self.audio_quality = audio_quality,
-            Message::VideoCodec(video_codec) => self.video_codec = video_codec,
-            Message::AudioCodec(audio_codec) => self.audio_codec = audio_codec,
-            Message::SelectFolder => {
+            Message::ToggleSettings => {
+                self.show_settings = !self.show_settings;
+                if self.show_settings && self.latest_version.is_none() && self.downloads.is_some() {
+                    return self.latest_version(false);
+                }
+            }
+            Message::UpdateConfig(config) => self.config = config,
+            Message::Url(message) => return self.update_url(message),
+            Message::Clip(message) => return self.update_clip(message),
+            Message::Downloads(message) => return self.update_downloads(message),
+            Message::Jobs(message) => return self.update_jobs(message),
+            Message::Folder(message) => return self.update_folder(message),
+            Message::Feeds(message) => return self.update_feeds(message),
+            Message::Schedule(message) => return self.update_schedule(message),
+            Message::Setup(message) => return self.update_setup(message),
+            Message::Ytdlp(message) => return self.update_ytdlp(message),
+            Message::Network(message) => return self.update_network(message),
+            Message::Presets(message) => return self.update_presets(message),
+            Message::Clipboard(message) => return self.update_clipboard(message),
+        }
+        Task::none()
+    }
+
+    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
+        Some(cosmic::applet::style())
+    }
+}
+
+impl Ytdlp {
+    fn update_url(&mut self, message: UrlMessage) -> Task<Message> {
+        match message {
+            UrlMessage::EnterURL(url) => {
+                self.url = url;
+                self.chapters.clear();
+                self.chapter = None;
+                self.chapters_error = None;
+                self.url_check += 1;
+                self.url_status = match urls::parse(&self.url) {
+                    Err(Invalid::Empty) => UrlStatus::Unchecked,
+                    Err(invalid) => UrlStatus::Invalid(invalid),
+                    Ok(_) => UrlStatus::Checking,
+                };
+                if self.url_status != UrlStatus::Checking {
+                    return Task::none();
+                }
+                // Wait for typing to stop before asking yt-dlp
+                let check = self.url_check;
+                return Task::perform(tokio::time::sleep(Duration::from_millis(500)), move |()| {
+                    Action::App(Message::Url(UrlMessage::CheckUrl(check)))
+                });
+            }
+            UrlMessage::CheckUrl(check) => {
+                if check != self.url_check {
+                    return Task::none();
+                }
+                let (Ok(url), Some(_)) = (urls::normalize(&self.url), &self.downloads) else {
+                    self.url_status = UrlStatus::Unchecked;
+                    return Task::none();
+                };
+                if let Some((expanded, support)) = self.url_checks.get(&url).cloned() {
+                    return self.update_url(UrlMessage::UrlChecked(check, url, expanded, support));
+                }
+                let binaries = self.binaries.clone();
+                let network = self.config.network.clone();
+                return Task::perform(
+                    async move {
+                        let expanded = urls::expand(&network, &url).await;
+                        let support = fetcher::extractor(&binaries, &network, &expanded).await;
+                        (url, expanded, support)
+                    },
+                    move |(url, expanded, support)| {
+                        Action::App(Message::Url(UrlMessage::UrlChecked(
+                            check, url, expanded, support,
+                        )))
+                    },
+                );
+            }
+            UrlMessage::UrlChecked(check, typed, url, support) => {
+                // Failed checks may work later, e.g. once back online
+                if support != Support::Unknown {
+                    self.url_checks
+                        .insert(typed, (url.clone(), support.clone()));
+                }
+                if check == self.url_check {
+                    self.url = url;
+                    self.url_status = match support {
+                        Support::Extractor(extractor) => UrlStatus::Supported(extractor),
+                        Support::Generic => UrlStatus::Generic,
+                        Support::Unsupported => UrlStatus::Unsupported,
+                        Support::Unknown => UrlStatus::Unknown,
+                    };
+                }
+            }
+            UrlMessage::BatchMode(enabled) => self.batch_mode = enabled,
+            UrlMessage::EditBatch(action) => self.batch.perform(action),
+            UrlMessage::ImportList => {
+                self.batch_errors.clear();
+                let future = async {
+                    let request = SelectedFiles::open_file()
+                        .title(fl_str!("import-list"))
+                        .accept_label(fl_str!("import"))
+                        .filter(FileFilter::new(fl_str!("url-lists")).mimetype("text/plain"))
+                        .multiple(false)
+                        .modal(true)
+                        .send()
+                        .await
+                        .ok()?;
+                    let file = request.response().ok()?;
+                    let path = file.uris().first()?.to_file_path().ok()?;
+                    Some(
+                        tokio::fs::read_to_string(&path)
+                            .await
+                            .map_err(|err| format!("failed to read {}: {err}", path.display())),
+                    )
+                };
+                return Task::perform(future, |res| {
+                    Action::App(Message::Url(UrlMessage::ListImported(res)))
+                });
+            }
+            UrlMessage::ListImported(res) => match res {
+                Some(Ok(list)) => {
+                    self.submit_batch(&list);
+                }
+                Some(Err(err)) => self.batch_errors = vec![err],
+                None => {}
+            },
+        }
+        Task::none()
+    }
+
+    fn update_clip(&mut self, message: ClipMessage) -> Task<Message> {
+        match message {
+            ClipMessage::ClipStart(start) => {
+                self.clip_start = start;
+                self.chapter = None;
+            }
+            ClipMessage::ClipEnd(end) => {
+                self.clip_end = end;
+                self.chapter = None;
+            }
+            ClipMessage::LoadChapters => {
+                self.loading_chapters = true;
+                self.chapters_error = None;
+                let binaries = self.binaries.clone();
+                let url = self.url.trim().to_owned();
+                let (network, auth) = (self.config.network.clone(), self.config.auth.clone());
+                return Task::perform(
+                    async move {
+                        let login = metadata_login(&network, &auth, &url).await;
+                        fetcher::chapters(&binaries, login.args.clone(), &url).await
+                    },
+                    |res| Action::App(Message::Clip(ClipMessage::ChaptersLoaded(res))),
+                );
+            }
+            ClipMessage::ChaptersLoaded(res) => {
+                self.loading_chapters = false;
+                match res {
+                    Ok(chapters) if chapters.is_empty() => {
+                        self.chapters_error = Some(fl!("no-chapters"));
+                    }
+                    Ok(chapters) => self.chapters = chapters,
+                    Err(err) => self.chapters_error = Some(err),
+                }
+            }
+            ClipMessage::Chapter(chapter) => {
+                let Clip { start, end } = chapter.clip();
+                self.clip_start = start.map(clip::format_timestamp).unwrap_or_default();
+                self.clip_end = end.map(clip::format_timestamp).unwrap_or_default();
+                self.chapter = Some(chapter);
+            }
+            ClipMessage::SplitChapters(enabled) => self.set_split(|split| split.enabled = enabled),
+            ClipMessage::ChapterTemplate(template) => {
+                self.set_split(|split| split.template = template);
+            }
+            ClipMessage::ChapterPlaylist(playlist) => {
+                self.set_split(|split| split.playlist = playlist);
+            }
+            ClipMessage::SponsorBlockApi(url) => {
+                self.set_sponsorblock(|sponsorblock| sponsorblock.api_url = url);
+            }
+            ClipMessage::SegmentAction(category, action) => {
+                self.set_sponsorblock(|sponsorblock| sponsorblock.set_action(category, action));
+            }
+        }
+        Task::none()
+    }
+
+    fn update_downloads(&mut self, message: DownloadsMessage) -> Task<Message> {
+        match message {
+            DownloadsMessage::ChangeType(id) => {
+                self.download_type.activate(id);
+                self.check_folder();
+            }
+            DownloadsMessage::DownloadAnyway(id) => {
+                let Some(i) = self.skipped.iter().position(|(skipped, _)| *skipped == id) else {
+                    return Task::none();
+                };
+                let (_, job) = self.skipped.remove(i);
+                let options = DownloadOptions {
+                    ignore_archive: true,
+                    ..job.options
+                };
+                self.submit(job.url, options, None);
+            }
+            DownloadsMessage::DismissSkipped(id) => {
+                self.skipped.retain(|(skipped, _)| *skipped != id);
+            }
+            DownloadsMessage::Preset(name) => {
+                let name = if name == fl!("no-preset") {
+                    String::new()
+                } else {
+                    name
+                };
+                self.select_preset(name.clone());
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_last_preset(handler, name);
+                }
+            }
+            DownloadsMessage::VideoQuality(video_resolution) => {
+                self.video_quality = video_resolution;
+            }
+            DownloadsMessage::AudioQuality(audio_quality) => self.audio_quality = audio_quality,
+            DownloadsMessage::VideoCodec(video_codec) => self.video_codec = video_codec,
+            DownloadsMessage::AudioCodec(audio_codec) => self.audio_codec = audio_codec,
+            DownloadsMessage::Download if self.batch_mode => {
+                // Leave the lines that couldn't be queued for fixing
+                let list = self.batch.text();
+                let invalid = self.submit_batch(&list);
+                let invalid: Vec<String> = invalid.into_iter().map(|(_, line)| line).collect();
+                self.batch = text_editor::Content::with_text(&invalid.join("\n"));
+                self.start_at.clear();
+            }
+            DownloadsMessage::Download => {
+                let start_at = self.start_time();
+                let options = DownloadOptions {
+                    clip: self.clip(),
+                    ..self.options()
+                };
+                let url = std::mem::take(&mut self.url);
+                let url = urls::normalize(&url).unwrap_or(url);
+                self.url_status = UrlStatus::Unchecked;
+                self.url_check += 1;
+                self.start_at.clear();
+                self.clip_start.clear();
+                self.clip_end.clear();
+                self.chapters.clear();
+                self.chapter = None;
+                self.submit(url, options, start_at);
+            }
+            DownloadsMessage::StartAt(start_at) => self.start_at = start_at,
+            DownloadsMessage::Dbus(dbus::Request { id, url, options }) => {
+                let mut download_options = self.options();
+                for (key, value) in &options {
+                    // Already validated by the service
+                    let _ = download_options.set(key, value);
+                }
+                self.submit_queued(id, url, download_options, None);
+            }
+        }
+        Task::none()
+    }
+
+    fn update_jobs(&mut self, message: JobsMessage) -> Task<Message> {
+        match message {
+            JobsMessage::ExportArchive => {
+                self.archive_error = None;
+                let archive = self
+                    .downloads
+                    .as_ref()
+                    .map(|downloads| downloads.archive().clone())
+                    .unwrap_or_default();
+                let future = async move {
+                    let request = SelectedFiles::save_file()
+                        .title(fl_str!("export-archive"))
+                        .current_name("archive.txt")
+                        .modal(true)
+                        .send()
+                        .await
+                        .ok()?;
+                    let file = request.response().ok()?;
+                    let path = file.uris().first()?.to_file_path().ok()?;
+                    Some(archive.export(&path).await.err())
+                };
+                return Task::perform(future, |res| {
+                    Action::App(Message::Jobs(JobsMessage::ArchiveExported(res.flatten())))
+                });
+            }
+            JobsMessage::ArchiveExported(err) => self.archive_error = err,
+            JobsMessage::CancelJob(id) => {
+                if let Some(downloads) = &self.downloads {
+                    downloads.cancel(id);
+                }
+            }
+            JobsMessage::Job(download::Event { id, job }) => {
+                let scheduled = self.scheduled.iter().any(|(waiting, _)| *waiting == id);
+                if job.state.is_finished() && scheduled {
+                    self.scheduled.retain(|(waiting, _)| *waiting != id);
+                    self.save_scheduled();
+                }
+                if job.state.is_finished() {
+                    let (finished, running): (Vec<_>, Vec<_>) = std::mem::take(&mut self.running)
+                        .into_iter()
+                        .partition(|(running, _)| *running == id);
+                    self.running = running;
+                    // Downloading anyway keeps the preset, folder and clip it was queued with
+                    if job.state == JobState::Skipped {
+                        self.skipped.extend(finished);
+                    }
+                }
+                let (summary, body) = match job.state {
+                    JobState::Done => (
+                        fl!("finished-download", title = job.title),
+                        job.error.unwrap_or_default(),
+                    ),
+                    JobState::Failed => (job.error.unwrap_or_default(), String::new()),
+                    JobState::Skipped => {
+                        let summary = job.error.unwrap_or_default();
+                        return Task::future(async move {
+                            let anyway = fl!("download-anyway");
+                            if notifications::ask(&summary, &job.url, &anyway).await {
+                                Action::App(Message::Downloads(DownloadsMessage::DownloadAnyway(
+                                    id,
+                                )))
+                            } else {
+                                Action::App(Message::Downloads(DownloadsMessage::DismissSkipped(
+                                    id,
+                                )))
+                            }
+                        });
+                    }
+                    _ => return Task::none(),
+                };
+                return Task::future(async move {
+                    let _ = notification()
+                        .summary(&summary)
+                        .body(&body)
+                        .show_async()
+                        .await;
+                    Action::None
+                });
+            }
+        }
+        Task::none()
+    }
+
+    fn update_folder(&mut self, message: FolderMessage) -> Task<Message> {
+        match message {
+            FolderMessage::SelectFolder => {
                 let future = async {
                     let request = SelectedFiles::open_file()
                         .title("Download Folder")
@@ -219,151 +940,1775 @@ impl Application for Ytdlp {
                         .await
                         .ok()?;
                     let folder = request.response().ok()?;
-                    let uri = folder.uris().first()?;
-                    Some(uri.path().to_string())
+                    let path = folder.uris().first()?.to_file_path().ok()?;
+                    Some(path.to_string_lossy().into_owned())
                 };
                 return Task::perform(future, |folder| {
                     if let Some(folder) = folder {
-                        return Action::App(Message::ProcessSelectFolder(folder));
+                        return Action::App(Message::Folder(FolderMessage::ProcessSelectFolder(
+                            folder,
+                        )));
                     }
                     Action::App(Message::TogglePopup)
                 });
             }
-            Message::ProcessSelectFolder(folder) => {
-                let video_selected = self.video_entity == self.download_type.active();
-                if video_selected {
-                    self.video_folder = folder;
-                } else {
-                    self.audio_folder = folder;
-                }
+            FolderMessage::ProcessSelectFolder(folder) => {
+                self.set_folder(folder);
                 return Task::done(Action::App(Message::TogglePopup));
             }
-            Message::Download => {
-                self.downloading += 1;
-                let video_selected = self.video_entity == self.download_type.active();
+            FolderMessage::EnterFolder(folder) => self.set_folder(folder),
+            FolderMessage::CreateFolder => {
+                let folder = self.folder();
+                return Task::perform(
+                    async move {
+                        tokio::fs::create_dir_all(&folder).await.map_err(|err| {
+                            let err = format!("failed to create {}: {err}", folder.display());
+                            (folder, err)
+                        })
+                    },
+                    |res| Action::App(Message::Folder(FolderMessage::FolderCreated(res))),
+                );
+            }
+            FolderMessage::FolderCreated(res) => {
+                self.folder_error = res.err();
+                self.check_folder();
+            }
+        }
+        Task::none()
+    }
 
-                let url = self.url.clone();
-                let lib_dir = self.lib_dir.clone();
-                self.url.clear();
-                let output_dir = PathBuf::from(if video_selected {
-                    &self.video_folder
+    fn update_feeds(&mut self, message: FeedsMessage) -> Task<Message> {
+        match message {
+            FeedsMessage::FeedUrl(url) => self.feed_draft.url = url,
+            FeedsMessage::FeedPreset(name) => {
+                self.feed_draft.preset = if name == fl!("no-preset") {
+                    String::new()
                 } else {
-                    &self.audio_folder
-                });
-                let video_quality = self.video_quality.into();
-                let video_codec = self.video_codec.into();
-                let audio_quality = self.audio_quality.into();
-                let audio_codec = self.audio_codec.into();
-                return Task::future(async move {
-                    let mut notify = Notification::new()
-                        .appname("yt-dlp applet")
-                        .icon("multimedia-video-player-symbolic")
-                        .finalize();
-
-                    let fetcher = fetcher::with_output_dir(&lib_dir, output_dir);
-                    let res = fetcher.fetch_video_infos(url.clone()).await;
-                    let Ok(video) = res else {
-                        let _ = notify
-                            .summary(fl_str!("metadata-failed"))
-                            .show_async()
-                            .await;
-                        return Action::App(Message::Finished);
-                    };
-                    let title = video.filename.rsplit_once('.').unwrap().0.to_string();
+                    name
+                };
+            }
+            FeedsMessage::FeedFolder(folder) => self.feed_draft.folder = folder,
+            FeedsMessage::FeedExisting(existing) => self.feed_existing = existing,
+            FeedsMessage::AddFeed => {
+                let url = self.feed_draft.url.trim().to_owned();
+                if url.is_empty() || self.adding_feed || self.downloads.is_none() {
+                    return Task::none();
+                }
+                self.adding_feed = true;
+                self.feed_error = None;
+                let binaries = self.binaries.clone();
+                let (network, auth) = (self.config.network.clone(), self.config.auth.clone());
+                let Feed { preset, folder, .. } = self.feed_draft.clone();
+                let existing = self.feed_existing;
+                return Task::perform(
+                    async move {
+                        let login = metadata_login(&network, &auth, &url).await;
+                        let args = login.args.clone();
+                        Feed::subscribe(&binaries, args, url, preset, folder, existing).await
+                    },
+                    |res| Action::App(Message::Feeds(FeedsMessage::FeedAdded(res))),
+                );
+            }
+            FeedsMessage::FeedAdded(res) => {
+                self.adding_feed = false;
+                match res {
+                    Ok(feed) => {
+                        self.feed_draft = Feed::default();
+                        let mut feeds = self.config.feeds.clone();
+                        feeds.retain(|saved| saved.url != feed.url);
+                        feeds.push(feed);
+                        if let Some(handler) = &self.config_handler {
+                            let _ = self.config.set_feeds(handler, feeds);
+                        }
+                    }
+                    Err(err) => self.feed_error = Some(err),
+                }
+            }
+            FeedsMessage::RemoveFeed(index) => {
+                let mut feeds = self.config.feeds.clone();
+                if index < feeds.len() {
+                    feeds.remove(index);
+                    if let Some(handler) = &self.config_handler {
+                        let _ = self.config.set_feeds(handler, feeds);
+                    }
+                }
+            }
+            FeedsMessage::FeedInterval(interval) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_feed_interval(handler, interval);
+                }
+            }
+            FeedsMessage::PollFeeds => {
+                if !self.config.feeds_due() {
+                    return Task::none();
+                }
+                return self.check_feeds();
+            }
+            FeedsMessage::CheckFeeds => return self.check_feeds(),
+            FeedsMessage::FeedsChecked(found) => return self.feeds_checked(found),
+        }
+        Task::none()
+    }
 
-                    let (Some(format), extension) = (if video_selected {
-                        (video.select_video_format(video_quality, video_codec), "mp4")
-                    } else {
-                        (
-                            video.select_audio_format(audio_quality, audio_codec),
-                            ".m4a",
-                        )
-                    }) else {
-                        let _ = notify.summary(fl_str!("missing-format")).show_async().await;
-                        return Action::App(Message::Finished);
+    fn update_schedule(&mut self, message: ScheduleMessage) -> Task<Message> {
+        match message {
+            ScheduleMessage::RunScheduled => self.run_scheduled(),
+            ScheduleMessage::WindowEnabled(enabled) => {
+                let window = Window {
+                    enabled,
+                    ..self.config.window.clone()
+                };
+                self.set_window(window);
+            }
+            ScheduleMessage::WindowStart(start) => {
+                if let Some(minutes) = schedule::parse_time(&start) {
+                    let window = Window {
+                        start: minutes,
+                        ..self.config.window.clone()
                     };
-                    let download_failed = {
-                        let title = title.clone();
-                        async || {
-                            let _ = notify
-                                .summary(fl_str!("download-failed", title = title))
-                                .show_async()
-                                .await;
-                            Action::App(Message::Finished)
-                        }
+                    self.set_window(window);
+                }
+                self.window_start = start;
+            }
+            ScheduleMessage::WindowEnd(end) => {
+                if let Some(minutes) = schedule::parse_time(&end) {
+                    let window = Window {
+                        end: minutes,
+                        ..self.config.window.clone()
                     };
+                    self.set_window(window);
+                }
+                self.window_end = end;
+            }
+            ScheduleMessage::Conditions(conditions) => {
+                self.conditions = conditions;
+                self.apply_power();
+            }
+            ScheduleMessage::MeteredPolicy(policy) => {
+                self.set_power(|power| power.metered = policy);
+            }
+            ScheduleMessage::BatteryPolicy(policy) => {
+                self.set_power(|power| power.battery = policy);
+            }
+            ScheduleMessage::BatteryBelow(below) => {
+                if let Ok(below) = parse_optional::<u8>(&below) {
+                    self.set_power(|power| power.battery_below = below.map(|below| below.min(100)));
+                }
+            }
+        }
+        Task::none()
+    }
 
-                    if format.is_manifest() {
-                        if !fetcher::manifest(
-                            url,
-                            fetcher.output_dir,
-                            fetcher.libraries.ffmpeg,
-                            &title,
-                            video_selected,
-                        )
-                        .await
-                        {
-                            return download_failed().await;
+    fn update_setup(&mut self, message: SetupMessage) -> Task<Message> {
+        match message {
+            SetupMessage::Event(SetupEvent::Step(step)) => self.setup = Some(step),
+            SetupMessage::Event(SetupEvent::Done(res)) => {
+                self.setup = None;
+                match res {
+                    Ok(binaries) => {
+                        if let Some(downloads) = &self.downloads {
+                            downloads.backend().set_binaries(binaries.clone());
+                        } else {
+                            let downloads =
+                                DownloadService::new(YtdlpBackend::new(binaries.clone()));
+                            // Pick up where the last session left off
+                            let (network, auth) = (&self.config.network, &self.config.auth);
+                            self.scheduled = self
+                                .config
+                                .scheduled
+                                .iter()
+                                .map(|job| {
+                                    let id = downloads.queue(job.url.clone());
+                                    (id, job.restored(network, auth))
+                                })
+                                .collect();
+                            self.downloads = Some(downloads);
+                            self.run_scheduled();
                         }
-                    } else if fetcher
-                        .download_format(format, format!("{title}.{extension}"))
-                        .await
-                        .is_err()
-                    {
-                        return download_failed().await;
+                        self.binaries = binaries.clone();
+                        let unverified = binaries.unverified.join(", ");
+                        let quarantined = binaries.quarantined.clone();
+                        return Task::batch([
+                            Task::perform(
+                                async move { fetcher::ytdlp_version(&binaries).await },
+                                |version| {
+                                    Action::App(Message::Ytdlp(YtdlpMessage::YtdlpVersion(version)))
+                                },
+                            ),
+                            Task::done(Action::App(Message::Ytdlp(YtdlpMessage::CheckUpdate))),
+                            Task::future(async move {
+                                if !unverified.is_empty() {
+                                    let summary = fl!("import-unverified", binaries = unverified);
+                                    let _ = notification().summary(&summary).show_async().await;
+                                }
+                                for file in quarantined {
+                                    let file = file.to_string_lossy();
+                                    let summary = fl!("quarantined", file = file);
+                                    let _ = notification().summary(&summary).show_async().await;
+                                }
+                                Action::None
+                            }),
+                        ]);
                     }
-
-                    let _ = notify
-                        .summary(fl_str!("finished-download", title = title))
-                        .show_async()
-                        .await;
-                    Action::App(Message::Finished)
+                    Err(err) => self.setup_error = Some(err),
+                }
+            }
+            SetupMessage::RetrySetup => {
+                self.setup_error = None;
+                self.setup = Some(SetupStep::Checking);
+                return Self::setup(self.config.clone());
+            }
+            SetupMessage::BinarySource(source) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_binary_source(handler, source);
+                }
+                return Task::done(Action::App(Message::Setup(SetupMessage::RetrySetup)));
+            }
+            SetupMessage::CustomYtdlp(path) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_custom_yt_dlp(handler, path);
+                }
+            }
+            SetupMessage::CustomFfmpeg(path) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_custom_ffmpeg(handler, path);
+                }
+            }
+            SetupMessage::ImportPath(path) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_import_path(handler, path);
+                }
+            }
+            SetupMessage::ImportDeps => {
+                if self.setup.is_some() || self.config.import_path.is_empty() {
+                    return Task::none();
+                }
+                self.setup = Some(SetupStep::Importing);
+                self.setup_error = None;
+                let path = folders::expand(&self.config.import_path);
+                return Task::perform(async move { fetcher::import(&path).await }, |res| {
+                    Action::App(Message::Setup(SetupMessage::Event(SetupEvent::Done(res))))
                 });
             }
-            Message::Finished => self.downloading -= 1,
         }
         Task::none()
     }
 
-    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
-        Some(cosmic::applet::style())
-    }
-}
-
-impl Ytdlp {
-    fn view_video(&self) -> Element<Message> {
-        column![
-            row![
-                body(fl!("video-quality")).width(Length::FillPortion(1)),
-                pick_list(
-                    vec![
-                        VideoQuality::Highest,
-                        VideoQuality::FHD,
-                        VideoQuality::HD,
-                        VideoQuality::SD,
-                        VideoQuality::Lowest,
-                    ],
-                    Some(self.video_quality),
-                    Message::VideoQuality
-                )
-                .width(Length::FillPortion(1)),
+    fn update_ytdlp(&mut self, message: YtdlpMessage) -> Task<Message> {
+        match message {
+            YtdlpMessage::YtdlpVersion(version) => self.ytdlp_version = version,
+            YtdlpMessage::CheckUpdate => {
+                if !self.config.update_due() {
+                    return Task::none();
+                }
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_last_update_check(handler, config::now());
+                }
+                return self.latest_version(true);
+            }
+            YtdlpMessage::UpdateChecked(latest, auto) => {
+                self.latest_version = latest;
+                // Don't swap the binary under running downloads
+                let idle = self
+                    .downloads
+                    .as_ref()
+                    .is_some_and(|downloads| downloads.jobs().active() == 0);
+                if auto
+                    && idle
+                    && self.binaries.bundled
+                    && self.latest_version.is_some()
+                    && self.latest_version != self.ytdlp_version
+                {
+                    return Task::done(Action::App(Message::Ytdlp(YtdlpMessage::UpdateYtdlp)));
+                }
+            }
+            YtdlpMessage::UpdateYtdlp => {
+                if self.updating || self.downloads.is_none() || !self.binaries.bundled {
+                    return Task::none();
+                }
+                self.updating = true;
+                self.update_error = None;
+                let binaries = self.binaries.clone();
+                let network = self.config.network.clone();
+                return Task::perform(
+                    async move { fetcher::update_ytdlp(&binaries, &network).await },
+                    |res| Action::App(Message::Ytdlp(YtdlpMessage::Updated(res))),
+                );
+            }
+            YtdlpMessage::Updated(res) => {
+                self.updating = false;
+                match res {
+                    Ok(version) => self.ytdlp_version = Some(version),
+                    Err(err) => self.update_error = Some(err),
+                }
+            }
+            YtdlpMessage::UpdateInterval(interval) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_update_interval(handler, interval);
+                }
+            }
+        }
+        Task::none()
+    }
+
+    fn update_network(&mut self, message: NetworkMessage) -> Task<Message> {
+        match message {
+            NetworkMessage::Proxy(proxy) => {
+                self.set_network(|network| network.proxy = proxy);
+            }
+            NetworkMessage::RateLimit(limit) => {
+                self.set_network(|network| network.rate_limit = limit);
+            }
+            NetworkMessage::SourceAddress(address) => {
+                self.set_network(|network| network.source_address = address);
+            }
+            NetworkMessage::IpVersion(version) => {
+                self.set_network(|network| network.ip_version = version);
+            }
+            NetworkMessage::NetworkTimeout(timeout) => {
+                // Ignore anything that isn't a number, empty means the default
+                if let Ok(timeout) = parse_optional(&timeout) {
+                    self.set_network(|network| network.timeout = timeout);
+                }
+            }
+            NetworkMessage::Retries(retries) => {
+                if let Ok(retries) = parse_optional(&retries) {
+                    self.set_network(|network| network.retries = retries);
+                }
+            }
+            NetworkMessage::CookiesFile(file) => {
+                self.set_auth(|auth| auth.cookies_file = file);
+            }
+            NetworkMessage::Netrc(netrc) => {
+                self.set_auth(|auth| auth.netrc = netrc);
+            }
+            NetworkMessage::CredentialDomain(domain) => self.credential.domain = domain,
+            NetworkMessage::CredentialUsername(username) => self.credential.username = username,
+            NetworkMessage::CredentialPassword(password) => self.credential_password = password,
+            NetworkMessage::SaveCredential => {
+                let credential = Credential {
+                    domain: self.credential.domain.trim().to_owned(),
+                    username: self.credential.username.trim().to_owned(),
+                };
+                let password = std::mem::take(&mut self.credential_password);
+                if credential.domain.is_empty() || credential.username.is_empty() {
+                    return Task::none();
+                }
+                self.auth_error = None;
+                return Task::perform(
+                    async move {
+                        auth::store_password(credential.domain.clone(), password)
+                            .await
+                            .map(|()| credential)
+                    },
+                    |res| Action::App(Message::Network(NetworkMessage::CredentialSaved(res))),
+                );
+            }
+            NetworkMessage::CredentialSaved(res) => match res {
+                Ok(credential) => {
+                    self.credential = Credential::default();
+                    self.set_auth(|auth| {
+                        auth.credentials
+                            .retain(|saved| saved.domain != credential.domain);
+                        auth.credentials.push(credential);
+                    });
+                }
+                Err(err) => self.auth_error = Some(err),
+            },
+            NetworkMessage::RemoveCredential(index) => {
+                let Some(credential) = self.config.auth.credentials.get(index).cloned() else {
+                    return Task::none();
+                };
+                self.set_auth(|auth| {
+                    auth.credentials.remove(index);
+                });
+                return Task::perform(auth::forget_password(credential.domain), |()| Action::None);
+            }
+        }
+        Task::none()
+    }
+
+    fn update_presets(&mut self, message: PresetsMessage) -> Task<Message> {
+        match message {
+            PresetsMessage::PresetName(name) => self.preset_draft.name = name,
+            PresetsMessage::PresetTemplate(template) => {
+                self.preset_draft.filename_template = template;
+            }
+            PresetsMessage::PresetArgs(args) => self.preset_draft.extra_args = args,
+            PresetsMessage::SavePreset => {
+                let name = self.preset_draft.name.trim().to_owned();
+                if name.is_empty() || presets::split_args(&self.preset_draft.extra_args).is_err() {
+                    return Task::none();
+                }
+                let options = self.options();
+                let preset = Preset {
+                    name: name.clone(),
+                    video: options.video,
+                    video_quality: options.video_quality,
+                    video_codec: options.video_codec,
+                    audio_quality: options.audio_quality,
+                    audio_codec: options.audio_codec,
+                    folder: options.output_dir.to_string_lossy().into_owned(),
+                    ..std::mem::take(&mut self.preset_draft)
+                };
+                let mut presets = self.config.presets.clone();
+                presets.retain(|saved| saved.name != name);
+                presets.push(preset);
+                self.preset = name.clone();
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_presets(handler, presets);
+                    let _ = self.config.set_last_preset(handler, name);
+                }
+            }
+            PresetsMessage::RemovePreset(index) => {
+                let mut presets = self.config.presets.clone();
+                if index < presets.len() {
+                    let removed = presets.remove(index);
+                    if removed.name == self.preset {
+                        self.preset.clear();
+                    }
+                    if let Some(handler) = &self.config_handler {
+                        let _ = self.config.set_presets(handler, presets);
+                    }
+                }
+            }
+            PresetsMessage::RuleKind(kind) => self.rule_draft.kind = kind,
+            PresetsMessage::RuleField(field) => self.rule_draft.field = field,
+            PresetsMessage::RulePattern(pattern) => self.rule_draft.pattern = pattern,
+            PresetsMessage::RuleFolder(folder) => self.rule_draft.folder = folder,
+            PresetsMessage::AddRule => {
+                let mut rules = self.config.folder_rules.clone();
+                rules.push(std::mem::take(&mut self.rule_draft));
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_folder_rules(handler, rules);
+                }
+            }
+            PresetsMessage::RemoveRule(index) => {
+                let mut rules = self.config.folder_rules.clone();
+                if index < rules.len() {
+                    rules.remove(index);
+                    if let Some(handler) = &self.config_handler {
+                        let _ = self.config.set_folder_rules(handler, rules);
+                    }
+                }
+            }
+        }
+        Task::none()
+    }
+
+    fn update_clipboard(&mut self, message: ClipboardMessage) -> Task<Message> {
+        match message {
+            ClipboardMessage::ClipboardWatch(enabled) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_clipboard_watch(handler, enabled);
+                }
+                if !enabled {
+                    self.clipboard_url = None;
+                }
+            }
+            ClipboardMessage::DomainAllow(domains) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_domain_allow(handler, split_list(&domains));
+                }
+            }
+            ClipboardMessage::DomainDeny(domains) => {
+                if let Some(handler) = &self.config_handler {
+                    let _ = self.config.set_domain_deny(handler, split_list(&domains));
+                }
+            }
+            ClipboardMessage::PollClipboard => {
+                return Task::perform(clipboard::read(), |text| {
+                    Action::App(Message::Clipboard(ClipboardMessage::ClipboardChanged(text)))
+                });
+            }
+            ClipboardMessage::ClipboardChanged(text) => {
+                let Some(text) = text.filter(|text| *text != self.clipboard_last) else {
+                    return Task::none();
+                };
+                let candidate = clipboard::candidate(&text, &self.config);
+                self.clipboard_last = text;
+                let (Some(url), Some(_)) = (candidate, &self.downloads) else {
+                    return Task::none();
+                };
+                // Offered as it is, yt-dlp is only asked about it once accepted
+                self.clipboard_url = Some(url.clone());
+                return Task::future(async move {
+                    if notifications::ask(&fl!("clipboard-found"), &url, &fl!("download")).await {
+                        Action::App(Message::Clipboard(ClipboardMessage::DownloadClipboard))
+                    } else {
+                        Action::None
+                    }
+                });
+            }
+            ClipboardMessage::Dropped(dropped) => {
+                for url in dropped.iter().filter_map(|url| urls::normalize(url).ok()) {
+                    self.submit(url, self.options(), None);
+                }
+            }
+            ClipboardMessage::DownloadClipboard => {
+                let Some(url) = self.clipboard_url.take() else {
+                    return Task::none();
+                };
+                let binaries = self.binaries.clone();
+                let network = self.config.network.clone();
+                return Task::perform(
+                    async move {
+                        let support = fetcher::extractor(&binaries, &network, &url).await;
+                        (url, support)
+                    },
+                    |(url, support)| {
+                        Action::App(Message::Clipboard(ClipboardMessage::ClipboardChecked(
+                            url, support,
+                        )))
+                    },
+                );
+            }
+            ClipboardMessage::ClipboardChecked(url, Support::Unsupported) => {
+                return Task::future(async move {
+                    let _ = notification()
+                        .summary(&fl!("url-unsupported"))
+                        .body(&url)
+                        .show_async()
+                        .await;
+                    Action::None
+                });
+            }
+            ClipboardMessage::ClipboardChecked(url, _) => self.submit(url, self.options(), None),
+            ClipboardMessage::DismissClipboard => self.clipboard_url = None,
+        }
+        Task::none()
+    }
+
+    /// Queues what the feeds have that isn't downloading yet and says so.
+    fn feeds_checked(
+        &mut self,
+        found: Vec<(String, Result<Vec<String>, String>)>,
+    ) -> Task<Message> {
+        self.checking_feeds = false;
+        let Some(downloads) = &self.downloads else {
+            return Task::none();
+        };
+        let active = active_urls(downloads);
+        let mut added = vec![];
+        let mut sources = vec![];
+        let mut errors = vec![];
+        for (url, res) in found {
+            let Some(feed) = self.config.feeds.iter().find(|feed| feed.url == url) else {
+                continue;
+            };
+            let items = match res {
+                Ok(items) => items,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+            let items: Vec<String> = items
+                .into_iter()
+                .filter(|item| !active.contains(item))
+                .collect();
+            if items.is_empty() {
+                continue;
+            }
+            let options = self.feed_options(feed);
+            added.extend(items.into_iter().map(|item| (item, options.clone())));
+            sources.push(url);
+        }
+        self.feed_error = (!errors.is_empty()).then(|| errors.join("\n"));
+        if added.is_empty() {
+            return Task::none();
+        }
+        let summary = fl!("feeds-added", total = added.len());
+        for (url, options) in added {
+            self.submit(url, options, None);
+        }
+        let body = sources.join("\n");
+        Task::future(async move {
+            let _ = notification()
+                .summary(&summary)
+                .body(&body)
+                .show_async()
+                .await;
+            Action::None
+        })
+    }
+
+    fn setup(config: Config) -> Task<Message> {
+        Task::run(
+            stream::channel(4, |mut output| async move {
+                let progress = {
+                    let output = output.clone();
+                    move |step| {
+                        let _ = output.clone().try_send(SetupEvent::Step(step));
+                    }
+                };
+                let res = fetcher::binaries(&config, progress).await;
+                let _ = output.send(SetupEvent::Done(res)).await;
+            }),
+            |event| Action::App(Message::Setup(SetupMessage::Event(event))),
+        )
+    }
+
+    /// Looks up the latest yt-dlp release, updating to it if `auto` is set.
+    fn latest_version(&self, auto: bool) -> Task<Message> {
+        let latest = fetcher::latest_ytdlp_version(self.config.network.clone());
+        Task::perform(latest, move |latest| {
+            Action::App(Message::Ytdlp(YtdlpMessage::UpdateChecked(latest, auto)))
+        })
+    }
+
+    fn set_network(&mut self, change: impl FnOnce(&mut Network)) {
+        let mut network = self.config.network.clone();
+        change(&mut network);
+        if let Some(handler) = &self.config_handler {
+            let _ = self.config.set_network(handler, network);
+        }
+    }
+
+    fn set_split(&mut self, change: impl FnOnce(&mut Split)) {
+        let mut split = self.config.split.clone();
+        change(&mut split);
+        if let Some(handler) = &self.config_handler {
+            let _ = self.config.set_split(handler, split);
+        }
+    }
+
+    fn set_sponsorblock(&mut self, change: impl FnOnce(&mut SponsorBlock)) {
+        let mut sponsorblock = self.config.sponsorblock.clone();
+        change(&mut sponsorblock);
+        if let Some(handler) = &self.config_handler {
+            let _ = self.config.set_sponsorblock(handler, sponsorblock);
+        }
+    }
+
+    fn set_auth(&mut self, change: impl FnOnce(&mut Auth)) {
+        let mut auth = self.config.auth.clone();
+        change(&mut auth);
+        if let Some(handler) = &self.config_handler {
+            let _ = self.config.set_auth(handler, auth);
+        }
+    }
+
+    /// Unix time picked to start the next download at, if any.
+    fn start_time(&self) -> Option<i64> {
+        schedule::parse_time(&self.start_at)
+            .map(|minutes| schedule::next_time(minutes, Local::now()).timestamp())
+    }
+
+    /// Queues every new URL of a list, returning the lines that aren't web URLs.
+    fn submit_batch(&mut self, list: &str) -> Vec<(usize, String)> {
+        let Some(downloads) = &self.downloads else {
+            return vec![];
+        };
+        let batch = batch::parse(list, &active_urls(downloads));
+        let start_at = self.start_time();
+        for url in batch.urls {
+            self.submit(url, self.options(), start_at);
+        }
+        self.batch_errors = batch
+            .invalid
+            .iter()
+            .map(|(line, text)| fl!("invalid-line", line = *line, text = text.as_str()))
+            .collect();
+        if batch.duplicates > 0 {
+            let duplicates = fl!("duplicates-skipped", total = batch.duplicates);
+            self.batch_errors.push(duplicates);
+        }
+        batch.invalid
+    }
+
+    /// Starts a download now, or once its start time and the download window allow.
+    fn submit(&mut self, url: String, options: DownloadOptions, start_at: Option<i64>) {
+        let Some(downloads) = &self.downloads else {
+            return;
+        };
+        let id = downloads.queue(url.clone());
+        self.submit_queued(id, url, options, start_at);
+    }
+
+    fn submit_queued(
+        &mut self,
+        id: u32,
+        url: String,
+        options: DownloadOptions,
+        start_at: Option<i64>,
+    ) {
+        let Some(downloads) = &self.downloads else {
+            return;
+        };
+        let job = ScheduledJob {
+            url,
+            options,
+            start_at,
+        };
+        if job.due(&self.config.window) && !self.held() {
+            downloads.start(id, job.url.clone(), job.options.clone());
+            self.running.push((id, job));
+        } else {
+            self.scheduled.push((id, job));
+            self.save_scheduled();
+        }
+    }
+
+    fn run_scheduled(&mut self) {
+        let Some(downloads) = &self.downloads else {
+            return;
+        };
+        if self.held() {
+            return;
+        }
+        let window = &self.config.window;
+        let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.scheduled)
+            .into_iter()
+            .partition(|(_, job)| job.due(window));
+        self.scheduled = waiting;
+        if due.is_empty() {
+            return;
+        }
+        for (id, job) in due {
+            downloads.start(id, job.url.clone(), job.options.clone());
+            self.running.push((id, job));
+        }
+        self.save_scheduled();
+    }
+
+    fn save_scheduled(&mut self) {
+        let scheduled = self.scheduled.iter().map(|(_, job)| job.saved()).collect();
+        if let Some(handler) = &self.config_handler {
+            let _ = self.config.set_scheduled(handler, scheduled);
+        }
+    }
+
+    fn set_window(&mut self, window: Window) {
+        if let Some(handler) = &self.config_handler {
+            let _ = self.config.set_window(handler, window);
+        }
+        self.run_scheduled();
+    }
+
+    /// Whether the power policy keeps new downloads from starting.
+    fn held(&self) -> bool {
+        self.config.power.action(&self.conditions).0 != Policy::Ignore
+    }
+
+    fn set_power(&mut self, change: impl FnOnce(&mut PowerPolicy)) {
+        let mut power = self.config.power.clone();
+        change(&mut power);
+        if let Some(handler) = &self.config_handler {
+            let _ = self.config.set_power(handler, power);
+        }
+        self.apply_power();
+    }
+
+    /// Stops or resumes downloads after the conditions or the policy changed.
+    fn apply_power(&mut self) {
+        match self.config.power.action(&self.conditions).0 {
+            Policy::Ignore => self.run_scheduled(),
+            Policy::Defer => {}
+            Policy::Pause => {
+                let Some(downloads) = &self.downloads else {
+                    return;
+                };
+                // Restarted from scratch once downloads may run again
+                for (id, job) in std::mem::take(&mut self.running) {
+                    if downloads.cancel(id) {
+                        self.scheduled.push((downloads.queue(job.url.clone()), job));
+                    }
+                }
+                self.save_scheduled();
+            }
+        }
+    }
+
+    fn check_feeds(&mut self) -> Task<Message> {
+        let Some(archive) = self.downloads.as_ref().map(|d| d.archive().clone()) else {
+            return Task::none();
+        };
+        if self.checking_feeds {
+            return Task::none();
+        }
+        self.checking_feeds = true;
+        if let Some(handler) = &self.config_handler {
+            let _ = self.config.set_last_feed_check(handler, config::now());
+        }
+        let binaries = self.binaries.clone();
+        let feeds = self.config.feeds.clone();
+        let (network, auth) = (self.config.network.clone(), self.config.auth.clone());
+        Task::perform(
+            async move {
+                let mut found = vec![];
+                for feed in feeds {
+                    let login = metadata_login(&network, &auth, &feed.url).await;
+                    let items = feed
+                        .new_items(&binaries, login.args.clone(), &archive)
+                        .await;
+                    found.push((feed.url, items));
+                }
+                found
+            },
+            |found| Action::App(Message::Feeds(FeedsMessage::FeedsChecked(found))),
+        )
+    }
+
+    /// Options for new items of a feed: its preset and folder over the popup's selection.
+    fn feed_options(&self, feed: &Feed) -> DownloadOptions {
+        let mut options = self.options();
+        if let Some(preset) = self.config.preset(&feed.preset) {
+            options.output_dir = folders::expand(if preset.video {
+                &self.video_folder
+            } else {
+                &self.audio_folder
+            });
+            // Saved presets had their arguments checked when saved
+            let _ = preset.apply(&mut options);
+        }
+        if !feed.folder.trim().is_empty() {
+            options.output_dir = folders::expand(&feed.folder);
+        }
+        options
+    }
+
+    /// Loads a preset's options into the popup.
+    fn select_preset(&mut self, name: String) {
+        if let Some(preset) = self.config.preset(&name) {
+            self.download_type.activate(if preset.video {
+                self.video_entity
+            } else {
+                self.audio_entity
+            });
+            self.video_quality = preset.video_quality;
+            self.video_codec = preset.video_codec;
+            self.audio_quality = preset.audio_quality;
+            self.audio_codec = preset.audio_codec;
+            let folder = preset.folder.trim();
+            if !folder.is_empty() {
+                if preset.video {
+                    self.video_folder = folder.to_owned();
+                } else {
+                    self.audio_folder = folder.to_owned();
+                }
+            }
+            self.preset = name;
+        } else {
+            self.preset.clear();
+        }
+        self.check_folder();
+    }
+
+    /// Folder of the selected download type, with `~` expanded.
+    fn folder(&self) -> PathBuf {
+        if self.video_entity == self.download_type.active() {
+            folders::expand(&self.video_folder)
+        } else {
+            folders::expand(&self.audio_folder)
+        }
+    }
+
+    fn set_folder(&mut self, folder: String) {
+        if self.video_entity == self.download_type.active() {
+            self.video_folder = folder;
+        } else {
+            self.audio_folder = folder;
+        }
+        self.check_folder();
+    }
+
+    fn check_folder(&mut self) {
+        self.folder_problem = folders::check(&self.folder()).err();
+    }
+
+    fn options(&self) -> DownloadOptions {
+        let video = self.video_entity == self.download_type.active();
+        let mut options = DownloadOptions {
+            video,
+            video_quality: self.video_quality,
+            video_codec: self.video_codec,
+            audio_quality: self.audio_quality,
+            audio_codec: self.audio_codec,
+            output_dir: self.folder(),
+            network: self.config.network.clone(),
+            auth: self.config.auth.clone(),
+            split: self.config.split.clone(),
+            sponsorblock: self.config.sponsorblock.clone(),
+            folder_rules: self.config.folder_rules.clone(),
+            ..DownloadOptions::default()
+        };
+        // Type, quality and codec may have been changed since picking the preset
+        if let Some(preset) = self.config.preset(&self.preset) {
+            options.filename_template = preset.filename_template.trim().to_owned();
+            // Saved presets had their arguments checked when saved
+            options.extra_args = presets::split_args(&preset.extra_args).unwrap_or_default();
+        }
+        options
+    }
+
+    fn start_valid(&self) -> bool {
+        self.start_at.trim().is_empty() || schedule::parse_time(&self.start_at).is_some()
+    }
+
+    /// Range entered in the popup, with unparsable ends left open.
+    fn clip(&self) -> Clip {
+        Clip {
+            start: clip::parse_timestamp(&self.clip_start),
+            end: clip::parse_timestamp(&self.clip_end),
+        }
+    }
+
+    /// Whether there's something to download, clips only applying to single URLs.
+    /// URLs that couldn't be checked, or that only yt-dlp's generic extractor
+    /// matches, are left for yt-dlp to report on.
+    fn input_valid(&self) -> bool {
+        if self.batch_mode {
+            !self.batch.text().trim().is_empty()
+        } else {
+            let supported = match &self.url_status {
+                UrlStatus::Unchecked => !self.url.trim().is_empty(),
+                UrlStatus::Supported(_) | UrlStatus::Generic | UrlStatus::Unknown => true,
+                UrlStatus::Invalid(_) | UrlStatus::Checking | UrlStatus::Unsupported => false,
+            };
+            supported && self.clip_valid()
+        }
+    }
+
+    fn clip_valid(&self) -> bool {
+        let valid = |input: &str| input.trim().is_empty() || clip::parse_timestamp(input).is_some();
+        valid(&self.clip_start) && valid(&self.clip_end) && !self.clip().is_empty()
+    }
+
+    /// Waiting jobs and when they will start.
+    fn view_scheduled(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let reason = self.config.power.action(&self.conditions).1;
+        let jobs = self.scheduled.iter().map(|(id, job)| {
+            let title = self
+                .downloads
+                .as_ref()
+                .and_then(|downloads| downloads.jobs().get(*id))
+                .map_or_else(|| job.url.clone(), |info| info.title);
+            let status = match reason {
+                Some(reason) if job.due(&self.config.window) => reason.to_string(),
+                _ => {
+                    let starts = job.starts(&self.config.window).format("%a %H:%M");
+                    fl!("starts-at", time = starts.to_string())
+                }
+            };
+            row![
+                column![body(title), body(status)].width(Length::Fill),
+                button(body(fl!("cancel"))).on_press(Message::Jobs(JobsMessage::CancelJob(*id))),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control)
+            .into()
+        });
+        column![].extend(jobs).into()
+    }
+
+    fn view_preset(&self) -> Element<Message> {
+        let none = fl!("no-preset");
+        let mut names = vec![none.clone()];
+        names.extend(self.config.presets().into_iter().map(|preset| preset.name));
+        let selected = if self.preset.is_empty() {
+            none
+        } else {
+            self.preset.clone()
+        };
+        row![
+            body(fl!("preset")).width(Length::FillPortion(1)),
+            pick_list(names, Some(selected), |name| Message::Downloads(
+                DownloadsMessage::Preset(name)
+            ))
+            .width(Length::FillPortion(1)),
+        ]
+        .align_y(Alignment::Center)
+        .apply(padded_control)
+        .into()
+    }
+
+    fn view_setup(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let Spacing {
+            space_xxs, space_s, ..
+        } = cosmic::theme::active().cosmic().spacing;
+
+        let status: Element<Message> = if let Some(err) = &self.setup_error {
+            row![
+                column![body(fl!("yt-dlp-failed")), body(err.clone())]
+                    .spacing(space_xxs)
+                    .width(Length::Fill),
+                button(body(fl!("retry"))).on_press(Message::Setup(SetupMessage::RetrySetup)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .into()
+        } else if let Some(step) = self.setup {
+            body(match step {
+                SetupStep::Checking => fl!("setup-checking"),
+                SetupStep::InstallingYtdlp => fl!("setup-yt-dlp"),
+                SetupStep::InstallingFfmpeg => fl!("setup-ffmpeg"),
+                SetupStep::UpdatingYtdlp => fl!("setup-update"),
+                SetupStep::Importing => fl!("setup-import"),
+            })
+            .into()
+        } else {
+            return column![].into();
+        };
+
+        column![
+            padded_control(status),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+        ]
+        .into()
+    }
+
+    /// A single URL, or a list of them in batch mode.
+    fn view_url(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let input: Element<Message> = if self.batch_mode {
+            text_editor(&self.batch)
+                .placeholder(fl!("batch-placeholder"))
+                .on_action(|action| Message::Url(UrlMessage::EditBatch(action)))
+                .height(Length::Fixed(120.0))
+                .into()
+        } else {
+            text_input(fl!("url"), &self.url)
+                .on_input(|url| Message::Url(UrlMessage::EnterURL(url)))
+                .width(Length::Fill)
+                .into()
+        };
+        let status = match &self.url_status {
+            _ if self.batch_mode => None,
+            UrlStatus::Unchecked => None,
+            UrlStatus::Invalid(invalid) => Some(invalid.to_string()),
+            UrlStatus::Checking => Some(fl!("url-checking")),
+            UrlStatus::Supported(extractor) => {
+                Some(fl!("url-supported", extractor = extractor.as_str()))
+            }
+            UrlStatus::Generic => Some(fl!("url-generic")),
+            UrlStatus::Unsupported => Some(fl!("url-unsupported")),
+            UrlStatus::Unknown => Some(fl!("url-unknown")),
+        };
+        let errors = status
+            .into_iter()
+            .chain(self.batch_errors.iter().cloned())
+            .map(|error| body(error).apply(padded_control).into());
+
+        column![
+            input.apply(padded_control),
+            row![
+                body(fl!("batch")).width(Length::Fill),
+                toggler(self.batch_mode)
+                    .on_toggle(|enabled| Message::Url(UrlMessage::BatchMode(enabled))),
+                button(body(fl!("import-list"))).on_press(Message::Url(UrlMessage::ImportList)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+        ]
+        .extend(errors)
+        .into()
+    }
+
+    /// Why the folder can't be used, offering to create it when it's missing.
+    fn view_folder_problem(&self) -> Element<Message> {
+        let Some(problem) = self.folder_problem else {
+            return column![].into();
+        };
+        let pad = self.core.applet.suggested_padding(true);
+        let create = (problem == Problem::Missing).then(|| {
+            button(body(fl!("create-folder")))
+                .on_press(Message::Folder(FolderMessage::CreateFolder))
+        });
+
+        // A failed attempt to create the folder says more than it being missing
+        let folder = self.folder();
+        let reason = match &self.folder_error {
+            Some((failed, err)) if *failed == folder => err.clone(),
+            _ => problem.to_string(),
+        };
+        row![body(reason).width(Length::Fill)]
+            .push_maybe(create)
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control)
+            .into()
+    }
+
+    fn view_clipboard(&self) -> Element<Message> {
+        let Some(url) = &self.clipboard_url else {
+            return column![].into();
+        };
+        let pad = self.core.applet.suggested_padding(true);
+        let Spacing {
+            space_xxs, space_s, ..
+        } = cosmic::theme::active().cosmic().spacing;
+
+        column![
+            column![body(fl!("clipboard-found")), body(url.clone())]
+                .spacing(space_xxs)
+                .apply(padded_control),
+            row![
+                button(body(fl!("dismiss")))
+                    .on_press(Message::Clipboard(ClipboardMessage::DismissClipboard)),
+                button(body(fl!("download")))
+                    .on_press(Message::Clipboard(ClipboardMessage::DownloadClipboard)),
+            ]
+            .spacing(pad)
+            .apply(padded_control),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+        ]
+        .into()
+    }
+
+    fn view_settings(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let Spacing {
+            space_xxs, space_s, ..
+        } = cosmic::theme::active().cosmic().spacing;
+
+        column![
+            row![
+                body(fl!("settings")).width(Length::Fill),
+                button(body(fl!("back"))).on_press(Message::ToggleSettings),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            row![
+                body(fl!("clipboard-watch")).width(Length::Fill),
+                toggler(self.config.clipboard_watch).on_toggle(|enabled| Message::Clipboard(
+                    ClipboardMessage::ClipboardWatch(enabled)
+                )),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            body(fl!("domain-allow")).apply(padded_control),
+            text_input(
+                "youtube.com, vimeo.com",
+                self.config.domain_allow.join(", ")
+            )
+            .on_input(|domains| Message::Clipboard(ClipboardMessage::DomainAllow(domains)))
+            .apply(padded_control),
+            body(fl!("domain-deny")).apply(padded_control),
+            text_input("", self.config.domain_deny.join(", "))
+                .on_input(|domains| Message::Clipboard(ClipboardMessage::DomainDeny(domains)))
+                .apply(padded_control),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            row![
+                column![
+                    body(fl!(
+                        "yt-dlp-version",
+                        version = self.ytdlp_version.as_deref().unwrap_or("?")
+                    )),
+                    body(fl!(
+                        "yt-dlp-latest",
+                        version = self.latest_version.as_deref().unwrap_or("?")
+                    )),
+                ]
+                .width(Length::Fill),
+                button(body(fl!("update-yt-dlp"))).on_press_maybe(
+                    (!self.updating && self.downloads.is_some() && self.binaries.bundled)
+                        .then_some(Message::Ytdlp(YtdlpMessage::UpdateYtdlp))
+                ),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            row![
+                body(fl!("update-interval")).width(Length::FillPortion(1)),
+                pick_list(
+                    UpdateInterval::ALL,
+                    Some(self.config.update_interval),
+                    |interval| Message::Ytdlp(YtdlpMessage::UpdateInterval(interval))
+                )
+                .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .apply(padded_control),
+            body(self.update_error.clone().unwrap_or_default()).apply(padded_control),
+            row![
+                body(fl!("binaries")).width(Length::FillPortion(1)),
+                pick_list(
+                    BinarySource::ALL,
+                    Some(self.config.binary_source),
+                    |source| Message::Setup(SetupMessage::BinarySource(source))
+                )
+                .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .apply(padded_control),
+            self.view_custom_binaries(),
+            body(fl!("import-path")).apply(padded_control),
+            row![
+                text_input("~/Downloads/yt-dlp-deps.tar.xz", &self.config.import_path)
+                    .on_input(|path| Message::Setup(SetupMessage::ImportPath(path)))
+                    .width(Length::Fill),
+                button(body(fl!("import"))).on_press_maybe(
+                    (self.setup.is_none() && !self.config.import_path.is_empty())
+                        .then_some(Message::Setup(SetupMessage::ImportDeps))
+                ),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_network(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_auth(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_presets(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_folder_rules(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_split_settings(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_sponsorblock(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_window_settings(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_power(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            self.view_feeds(),
+            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+            row![
+                body(fl!("archive")).width(Length::Fill),
+                button(body(fl!("export-archive")))
+                    .on_press(Message::Jobs(JobsMessage::ExportArchive)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            body(self.archive_error.clone().unwrap_or_default()).apply(padded_control),
+        ]
+        .padding(pad)
+        .apply(scrollable)
+        .into()
+    }
+
+    fn view_split_settings(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+
+        column![
+            row![
+                body(fl!("chapter-template")).width(Length::FillPortion(1)),
+                text_input(split::DEFAULT_TEMPLATE, &self.config.split.template)
+                    .on_input(|template| Message::Clip(ClipMessage::ChapterTemplate(template)))
+                    .width(Length::FillPortion(2)),
             ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
             .apply(padded_control),
             row![
-                body(fl!("video-codec")).width(Length::FillPortion(1)),
+                body(fl!("chapter-playlist")).width(Length::Fill),
+                toggler(self.config.split.playlist)
+                    .on_toggle(|playlist| Message::Clip(ClipMessage::ChapterPlaylist(playlist))),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+        ]
+        .into()
+    }
+
+    fn view_sponsorblock(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let sponsorblock = &self.config.sponsorblock;
+        let categories = Category::ALL.into_iter().map(|category| {
+            row![
+                body(category.to_string()).width(Length::FillPortion(1)),
+                pick_list(
+                    sponsorblock::Action::ALL,
+                    Some(sponsorblock.action(category)),
+                    move |action| Message::Clip(ClipMessage::SegmentAction(category, action))
+                )
+                .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control)
+            .into()
+        });
+
+        column![
+            body(fl!("sponsorblock")).apply(padded_control),
+            text_input(sponsorblock::DEFAULT_API_URL, &sponsorblock.api_url)
+                .on_input(|url| Message::Clip(ClipMessage::SponsorBlockApi(url)))
+                .apply(padded_control),
+        ]
+        .extend(categories)
+        .into()
+    }
+
+    fn view_window_settings(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+
+        column![
+            row![
+                body(fl!("download-window")).width(Length::Fill),
+                toggler(self.config.window.enabled).on_toggle(|enabled| Message::Schedule(
+                    ScheduleMessage::WindowEnabled(enabled)
+                )),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            row![
+                text_input("01:00", &self.window_start)
+                    .on_input(|start| Message::Schedule(ScheduleMessage::WindowStart(start)))
+                    .width(Length::Fill),
+                body("–"),
+                text_input("06:00", &self.window_end)
+                    .on_input(|end| Message::Schedule(ScheduleMessage::WindowEnd(end)))
+                    .width(Length::Fill),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+        ]
+        .into()
+    }
+
+    fn view_power(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let power = &self.config.power;
+
+        column![
+            row![
+                body(fl!("on-metered")).width(Length::FillPortion(1)),
+                pick_list(
+                    Policy::ALL,
+                    Some(power.metered),
+                    |policy| Message::Schedule(ScheduleMessage::MeteredPolicy(policy))
+                )
+                .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            row![
+                body(fl!("on-battery")).width(Length::FillPortion(1)),
+                pick_list(
+                    Policy::ALL,
+                    Some(power.battery),
+                    |policy| Message::Schedule(ScheduleMessage::BatteryPolicy(policy))
+                )
+                .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            row![
+                body(fl!("battery-below")).width(Length::FillPortion(1)),
+                text_input(
+                    fl!("any-charge"),
+                    power
+                        .battery_below
+                        .map(|below| below.to_string())
+                        .unwrap_or_default()
+                )
+                .on_input(|below| Message::Schedule(ScheduleMessage::BatteryBelow(below)))
+                .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+        ]
+        .into()
+    }
+
+    fn view_feeds(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+
+        let saved = self.config.feeds.iter().enumerate().map(|(index, feed)| {
+            let label = if feed.preset.is_empty() {
+                feed.url.clone()
+            } else {
+                format!("{} ({})", feed.url, feed.preset)
+            };
+            row![
+                body(label).width(Length::Fill),
+                button(body(fl!("remove")))
+                    .on_press(Message::Feeds(FeedsMessage::RemoveFeed(index))),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control)
+            .into()
+        });
+
+        let none = fl!("no-preset");
+        let mut presets = vec![none.clone()];
+        presets.extend(self.config.presets().into_iter().map(|preset| preset.name));
+        let preset = if self.feed_draft.preset.is_empty() {
+            none
+        } else {
+            self.feed_draft.preset.clone()
+        };
+
+        column![
+            row![
+                body(fl!("subscriptions")).width(Length::Fill),
+                button(body(fl!("check-now"))).on_press_maybe(
+                    (!self.checking_feeds
+                        && self.downloads.is_some()
+                        && !self.config.feeds.is_empty())
+                    .then_some(Message::Feeds(FeedsMessage::CheckFeeds))
+                ),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            row![
+                body(fl!("feed-interval")).width(Length::FillPortion(1)),
+                pick_list(
+                    UpdateInterval::ALL,
+                    Some(self.config.feed_interval),
+                    |interval| Message::Feeds(FeedsMessage::FeedInterval(interval))
+                )
+                .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .apply(padded_control),
+        ]
+        .extend(saved)
+        .push(
+            text_input(fl!("feed-url"), &self.feed_draft.url)
+                .on_input(|url| Message::Feeds(FeedsMessage::FeedUrl(url)))
+                .apply(padded_control),
+        )
+        .push(
+            row![
+                body(fl!("preset")).width(Length::FillPortion(1)),
+                pick_list(presets, Some(preset), |name| Message::Feeds(
+                    FeedsMessage::FeedPreset(name)
+                ))
+                .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .apply(padded_control),
+        )
+        .push(
+            text_input(fl!("folder"), &self.feed_draft.folder)
+                .on_input(|folder| Message::Feeds(FeedsMessage::FeedFolder(folder)))
+                .apply(padded_control),
+        )
+        .push(
+            row![
+                body(fl!("feed-existing")).width(Length::Fill),
+                toggler(self.feed_existing)
+                    .on_toggle(|existing| Message::Feeds(FeedsMessage::FeedExisting(existing))),
+                button(body(fl!("subscribe"))).on_press_maybe(
+                    (!self.adding_feed
+                        && self.downloads.is_some()
+                        && !self.feed_draft.url.trim().is_empty())
+                    .then_some(Message::Feeds(FeedsMessage::AddFeed))
+                ),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+        )
+        .push(body(self.feed_error.clone().unwrap_or_default()).apply(padded_control))
+        .into()
+    }
+
+    fn view_presets(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let args_error = presets::split_args(&self.preset_draft.extra_args).err();
+
+        let saved = self
+            .config
+            .presets
+            .iter()
+            .enumerate()
+            .map(|(index, preset)| {
+                row![
+                    body(preset.name.clone()).width(Length::Fill),
+                    button(body(fl!("remove")))
+                        .on_press(Message::Presets(PresetsMessage::RemovePreset(index))),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(pad)
+                .apply(padded_control)
+                .into()
+            });
+
+        column![body(fl!("presets")).apply(padded_control)]
+            .extend(saved)
+            .push(
+                text_input(fl!("preset-name"), &self.preset_draft.name)
+                    .on_input(|name| Message::Presets(PresetsMessage::PresetName(name)))
+                    .apply(padded_control),
+            )
+            .push(
+                text_input(
+                    "%(uploader)s - %(title)s",
+                    &self.preset_draft.filename_template,
+                )
+                .on_input(|template| Message::Presets(PresetsMessage::PresetTemplate(template)))
+                .apply(padded_control),
+            )
+            .push(
+                text_input(fl!("extra-args"), &self.preset_draft.extra_args)
+                    .on_input(|args| Message::Presets(PresetsMessage::PresetArgs(args)))
+                    .apply(padded_control),
+            )
+            .push_maybe(
+                args_error
+                    .clone()
+                    .map(|err| body(err).apply(padded_control)),
+            )
+            .push(
+                button(body(fl!("save-preset")))
+                    .on_press_maybe(
+                        (!self.preset_draft.name.trim().is_empty() && args_error.is_none())
+                            .then_some(Message::Presets(PresetsMessage::SavePreset)),
+                    )
+                    .apply(padded_control),
+            )
+            .into()
+    }
+
+    /// Folder rules, tried in order, and a form to add one.
+    fn view_folder_rules(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let draft = &self.rule_draft;
+
+        let saved = self
+            .config
+            .folder_rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let pattern = if rule.pattern.trim().is_empty() {
+                    fl!("rule-any-value")
+                } else {
+                    rule.pattern.trim().to_owned()
+                };
+                let summary = fl!(
+                    "folder-rule",
+                    kind = rule.kind.to_string(),
+                    field = rule.field.to_string(),
+                    pattern = pattern,
+                    folder = rule.folder.trim()
+                );
+                row![
+                    body(summary).width(Length::Fill),
+                    button(body(fl!("remove")))
+                        .on_press(Message::Presets(PresetsMessage::RemoveRule(index))),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(pad)
+                .apply(padded_control)
+                .into()
+            });
+
+        column![body(fl!("folder-rules")).apply(padded_control)]
+            .extend(saved)
+            .push(
+                row![
+                    pick_list(rules::Kind::ALL, Some(draft.kind), |kind| Message::Presets(
+                        PresetsMessage::RuleKind(kind)
+                    ))
+                    .width(Length::FillPortion(1)),
+                    pick_list(Field::ALL, Some(draft.field), |field| Message::Presets(
+                        PresetsMessage::RuleField(field)
+                    ))
+                    .width(Length::FillPortion(1)),
+                ]
+                .spacing(pad)
+                .apply(padded_control),
+            )
+            .push(
+                text_input(fl!("rule-pattern"), &draft.pattern)
+                    .on_input(|pattern| Message::Presets(PresetsMessage::RulePattern(pattern)))
+                    .apply(padded_control),
+            )
+            .push(
+                text_input("%(playlist)s", &draft.folder)
+                    .on_input(|folder| Message::Presets(PresetsMessage::RuleFolder(folder)))
+                    .apply(padded_control),
+            )
+            .push(
+                button(body(fl!("add-rule")))
+                    .on_press_maybe(
+                        (!draft.folder.trim().is_empty())
+                            .then_some(Message::Presets(PresetsMessage::AddRule)),
+                    )
+                    .apply(padded_control),
+            )
+            .into()
+    }
+
+    fn view_auth(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let auth = &self.config.auth;
+
+        let saved = auth
+            .credentials
+            .iter()
+            .enumerate()
+            .map(|(index, credential)| {
+                row![
+                    body(format!("{} ({})", credential.domain, credential.username))
+                        .width(Length::Fill),
+                    button(body(fl!("remove")))
+                        .on_press(Message::Network(NetworkMessage::RemoveCredential(index))),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(pad)
+                .apply(padded_control)
+                .into()
+            });
+
+        column![
+            body(fl!("cookies-file")).apply(padded_control),
+            text_input("~/cookies.txt", &auth.cookies_file)
+                .on_input(|file| Message::Network(NetworkMessage::CookiesFile(file)))
+                .apply(padded_control),
+            row![
+                body(fl!("use-netrc")).width(Length::Fill),
+                toggler(auth.netrc)
+                    .on_toggle(|netrc| Message::Network(NetworkMessage::Netrc(netrc))),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            body(fl!("logins")).apply(padded_control),
+        ]
+        .extend(saved)
+        .push(
+            row![
+                text_input(fl!("domain"), &self.credential.domain)
+                    .on_input(|domain| Message::Network(NetworkMessage::CredentialDomain(domain))),
+                text_input(fl!("username"), &self.credential.username).on_input(|username| {
+                    Message::Network(NetworkMessage::CredentialUsername(username))
+                }),
+                text_input(fl!("password"), &self.credential_password)
+                    .on_input(
+                        |password| Message::Network(NetworkMessage::CredentialPassword(password))
+                    )
+                    .password(),
+                button(body(fl!("save")))
+                    .on_press(Message::Network(NetworkMessage::SaveCredential)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+        )
+        .push(body(self.auth_error.clone().unwrap_or_default()).apply(padded_control))
+        .into()
+    }
+
+    fn view_network(&self) -> Element<Message> {
+        let network = &self.config.network;
+        let field = |label: String, input: Element<'static, Message>| {
+            row![body(label).width(Length::FillPortion(1)), input]
+                .align_y(Alignment::Center)
+                .apply(padded_control)
+        };
+        let number = |value: Option<String>, on_input: fn(String) -> Message| {
+            text_input(fl!("default"), value.unwrap_or_default())
+                .on_input(on_input)
+                .width(Length::FillPortion(1))
+                .into()
+        };
+
+        column![
+            field(
+                fl!("proxy"),
+                text_input("socks5://127.0.0.1:1080", network.proxy.clone())
+                    .on_input(|proxy| Message::Network(NetworkMessage::Proxy(proxy)))
+                    .width(Length::FillPortion(1))
+                    .into()
+            ),
+            field(
+                fl!("rate-limit"),
+                text_input("2M", network.rate_limit.clone())
+                    .on_input(|limit| Message::Network(NetworkMessage::RateLimit(limit)))
+                    .width(Length::FillPortion(1))
+                    .into()
+            ),
+            field(
+                fl!("source-address"),
+                text_input("", network.source_address.clone())
+                    .on_input(|address| Message::Network(NetworkMessage::SourceAddress(address)))
+                    .width(Length::FillPortion(1))
+                    .into()
+            ),
+            field(
+                fl!("ip-version"),
+                pick_list(IpVersion::ALL, Some(network.ip_version), |version| {
+                    Message::Network(NetworkMessage::IpVersion(version))
+                })
+                .width(Length::FillPortion(1))
+                .into()
+            ),
+            field(
+                fl!("timeout"),
+                number(
+                    network.timeout.map(|timeout| timeout.to_string()),
+                    |timeout| Message::Network(NetworkMessage::NetworkTimeout(timeout))
+                )
+            ),
+            field(
+                fl!("retries"),
+                number(
+                    network.retries.map(|retries| retries.to_string()),
+                    |retries| Message::Network(NetworkMessage::Retries(retries))
+                )
+            ),
+        ]
+        .into()
+    }
+
+    fn view_custom_binaries(&self) -> Element<Message> {
+        if self.config.binary_source != BinarySource::Custom {
+            return column![].into();
+        }
+        let pad = self.core.applet.suggested_padding(true);
+
+        column![
+            text_input("/usr/bin/yt-dlp", &self.config.custom_yt_dlp)
+                .on_input(|path| Message::Setup(SetupMessage::CustomYtdlp(path)))
+                .apply(padded_control),
+            text_input("/usr/bin/ffmpeg", &self.config.custom_ffmpeg)
+                .on_input(|path| Message::Setup(SetupMessage::CustomFfmpeg(path)))
+                .apply(padded_control),
+            row![
+                body(String::new()).width(Length::Fill),
+                button(body(fl!("apply"))).on_press(Message::Setup(SetupMessage::RetrySetup)),
+            ]
+            .spacing(pad)
+            .apply(padded_control),
+        ]
+        .into()
+    }
+
+    fn view_video(&self) -> Element<Message> {
+        column![
+            row![
+                body(fl!("video-quality")).width(Length::FillPortion(1)),
                 pick_list(
-                    vec![
-                        VideoCodec::AV1,
-                        VideoCodec::AVC1,
-                        VideoCodec::VP9,
-                        VideoCodec::Any,
-                    ],
-                    Some(self.video_codec),
-                    Message::VideoCodec
+                    VideoQuality::ALL,
+                    Some(self.video_quality),
+                    |video_resolution| Message::Downloads(DownloadsMessage::VideoQuality(
+                        video_resolution
+                    ))
                 )
                 .width(Length::FillPortion(1)),
             ]
             .apply(padded_control),
+            row![
+                body(fl!("video-codec")).width(Length::FillPortion(1)),
+                pick_list(VideoCodec::ALL, Some(self.video_codec), |video_codec| {
+                    Message::Downloads(DownloadsMessage::VideoCodec(video_codec))
+                })
+                .width(Length::FillPortion(1)),
+            ]
+            .apply(padded_control),
+            self.view_clip(),
         ]
         .into()
     }
@@ -373,35 +2718,121 @@ impl Ytdlp {
             row![
                 body(fl!("audio-quality")).width(Length::FillPortion(1)),
                 pick_list(
-                    vec![
-                        AudioQuality::Best,
-                        AudioQuality::High,
-                        AudioQuality::Medium,
-                        AudioQuality::Low,
-                        AudioQuality::Worst,
-                    ],
+                    AudioQuality::ALL,
                     Some(self.audio_quality),
-                    Message::AudioQuality
+                    |audio_quality| Message::Downloads(DownloadsMessage::AudioQuality(
+                        audio_quality
+                    ))
                 )
                 .width(Length::FillPortion(1)),
             ]
             .apply(padded_control),
             row![
                 body(fl!("audio-codec")).width(Length::FillPortion(1)),
-                pick_list(
-                    vec![
-                        AudioCodec::Opus,
-                        AudioCodec::ACC,
-                        AudioCodec::MP3,
-                        AudioCodec::Any,
-                    ],
-                    Some(self.audio_codec),
-                    Message::AudioCodec
-                )
+                pick_list(AudioCodec::ALL, Some(self.audio_codec), |audio_codec| {
+                    Message::Downloads(DownloadsMessage::AudioCodec(audio_codec))
+                })
+                .width(Length::FillPortion(1)),
+            ]
+            .apply(padded_control),
+            self.view_clip(),
+        ]
+        .into()
+    }
+
+    /// Time range and chapter to download, shared by both download types.
+    fn view_clip(&self) -> Element<Message> {
+        let pad = self.core.applet.suggested_padding(true);
+        let chapters: Element<Message> = if self.chapters.is_empty() {
+            let can_load =
+                self.downloads.is_some() && !self.loading_chapters && !self.url.trim().is_empty();
+            row![
+                body(self.chapters_error.clone().unwrap_or_default()).width(Length::Fill),
+                button(body(fl!("load-chapters")))
+                    .on_press_maybe(can_load.then_some(Message::Clip(ClipMessage::LoadChapters))),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .into()
+        } else {
+            row![
+                body(fl!("chapter")).width(Length::FillPortion(1)),
+                pick_list(self.chapters.as_slice(), self.chapter.clone(), |chapter| {
+                    Message::Clip(ClipMessage::Chapter(chapter))
+                })
                 .width(Length::FillPortion(1)),
             ]
+            .align_y(Alignment::Center)
+            .into()
+        };
+
+        column![
+            row![
+                body(fl!("clip")).width(Length::FillPortion(1)),
+                text_input(fl!("clip-start"), &self.clip_start)
+                    .on_input(|start| Message::Clip(ClipMessage::ClipStart(start)))
+                    .width(Length::FillPortion(1)),
+                body("–"),
+                text_input(fl!("clip-end"), &self.clip_end)
+                    .on_input(|end| Message::Clip(ClipMessage::ClipEnd(end)))
+                    .width(Length::FillPortion(1)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
+            .apply(padded_control),
+            chapters.apply(padded_control),
+            row![
+                body(fl!("split-chapters")).width(Length::Fill),
+                toggler(self.config.split.enabled)
+                    .on_toggle(|enabled| Message::Clip(ClipMessage::SplitChapters(enabled))),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(pad)
             .apply(padded_control),
         ]
         .into()
     }
 }
+
+fn notification() -> Notification {
+    Notification::new()
+        .appname(notifications::APP_NAME)
+        .icon(notifications::ICON)
+        .finalize()
+}
+
+/// URLs of jobs that haven't finished yet.
+fn active_urls(downloads: &DownloadService) -> Vec<String> {
+    downloads
+        .jobs()
+        .list()
+        .into_iter()
+        .filter(|(_, job)| !job.state.is_finished())
+        .map(|(_, job)| job.url)
+        .collect()
+}
+
+/// Login for looking up metadata of `url`, its arguments preceded by the network
+/// settings. The arguments stay valid while the login is alive.
+async fn metadata_login(network: &Network, auth: &Auth, url: &str) -> Login {
+    let mut login = auth.login(url).await;
+    login.args.splice(0..0, network.ytdlp_args());
+    login
+}
+
+/// Parses an optional number, where empty input means unset.
+fn parse_optional<T: std::str::FromStr>(input: &str) -> Result<Option<T>, T::Err> {
+    let input = input.trim();
+    if input.is_empty() {
+        Ok(None)
+    } else {
+        input.parse().map(Some)
+    }
+}
+
+/// Splits a comma separated settings field, keeping empty entries so typing isn't disrupted.
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|entry| entry.trim().to_owned())
+        .collect()
+}
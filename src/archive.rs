@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Record of finished downloads, one `extractor id` key per line.
+//!
+//! This is the format of yt-dlp's `--download-archive`, so an exported archive can be
+//! used with yt-dlp directly.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use cosmic::Application;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::applet::Ytdlp;
+
+#[derive(Debug, Clone)]
+pub struct Archive {
+    path: Option<PathBuf>,
+    /// Serializes writes from concurrent jobs.
+    lock: Arc<Mutex<()>>,
+}
+
+impl Default for Archive {
+    fn default() -> Self {
+        let path = xdg::BaseDirectories::with_prefix(Ytdlp::APP_ID)
+            .ok()
+            .and_then(|dirs| dirs.place_data_file("archive.txt").ok());
+        Self::new(path)
+    }
+}
+
+impl Archive {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            lock: Arc::default(),
+        }
+    }
+
+    /// Builds a key the way yt-dlp does, from the extractor and the id it reports.
+    pub fn key(extractor: &str, id: &str) -> String {
+        format!("{} {id}", extractor.to_lowercase())
+    }
+
+    pub async fn contains(&self, key: &str) -> bool {
+        let Some(path) = &self.path else {
+            return false;
+        };
+        let contents = tokio::fs::read_to_string(path).await.unwrap_or_default();
+        contents.lines().any(|line| line.trim() == key)
+    }
+
+    pub async fn add(&self, key: &str) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let _guard = self.lock.lock().await;
+        if self.contains(key).await {
+            return Ok(());
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|err| format!("failed to open {}: {err}", path.display()))?;
+        file.write_all(format!("{key}\n").as_bytes())
+            .await
+            .map_err(|err| format!("failed to write {}: {err}", path.display()))
+    }
+
+    /// Copies the archive to `to`, creating an empty one if nothing was downloaded yet.
+    pub async fn export(&self, to: &Path) -> Result<(), String> {
+        let contents = match &self.path {
+            Some(path) => tokio::fs::read(path).await.unwrap_or_default(),
+            None => vec![],
+        };
+        tokio::fs::write(to, contents)
+            .await
+            .map_err(|err| format!("failed to write {}: {err}", to.display()))
+    }
+}
@@ -0,0 +1,398 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Credentials for content behind a login.
+//!
+//! Usernames live in the config, passwords in the Secret Service keyring under the
+//! applet's id and the site's domain.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use cosmic::Application;
+use reqwest::header::{AUTHORIZATION, COOKIE, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use tempfile::TempPath;
+use url::Url;
+
+use crate::applet::Ytdlp;
+use crate::folders;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credential {
+    pub domain: String,
+    pub username: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Auth {
+    /// Cookies exported from a browser, in Netscape `cookies.txt` format.
+    pub cookies_file: String,
+    /// Whether yt-dlp reads logins from `~/.netrc`.
+    pub netrc: bool,
+    pub credentials: Vec<Credential>,
+}
+
+/// A cookie from a `cookies.txt` file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Cookie {
+    domain: String,
+    path: String,
+    /// Whether it may only be sent over https.
+    secure: bool,
+    /// Unix time it expires at, 0 for session cookies.
+    expires: u64,
+    /// `name=value`, as sent.
+    pair: String,
+}
+
+impl Cookie {
+    /// Whether the cookie belongs with a request to `url` at Unix time `now`.
+    fn matches(&self, url: &Url, now: u64) -> bool {
+        url.host_str()
+            .is_some_and(|host| matches_domain(host, &self.domain))
+            && matches_path(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && (self.expires == 0 || self.expires > now)
+    }
+}
+
+/// What a single download needs to log in.
+#[derive(Debug, Default, Clone)]
+pub struct Login {
+    /// Arguments for yt-dlp, valid while the login is alive.
+    pub args: Vec<String>,
+    /// Cookies from the cookies file.
+    cookies: Vec<Cookie>,
+    /// Site login, as the domain and `Authorization` value.
+    basic: Option<(String, HeaderValue)>,
+    /// yt-dlp config file holding the site login, so the password stays off the
+    /// command line.
+    config: Option<Arc<TempPath>>,
+}
+
+impl Login {
+    /// Headers for a request made without yt-dlp, holding only the cookies and
+    /// login that belong to `url`. The login is only sent over https.
+    pub fn headers(&self, url: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let Some(url) = Url::parse(url).ok().filter(|url| url.host_str().is_some()) else {
+            return headers;
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let cookies: Vec<&str> = self
+            .cookies
+            .iter()
+            .filter(|cookie| cookie.matches(&url, now))
+            .map(|cookie| cookie.pair.as_str())
+            .collect();
+        let cookie = (!cookies.is_empty()).then(|| cookies.join("; "));
+        if let Some(mut value) = cookie.and_then(|cookie| HeaderValue::from_str(&cookie).ok()) {
+            value.set_sensitive(true);
+            headers.insert(COOKIE, value);
+        }
+        let basic = self.basic.as_ref().filter(|_| url.scheme() == "https");
+        let host = url.host_str().unwrap_or_default();
+        if let Some((_, value)) = basic.filter(|(domain, _)| matches_domain(host, domain)) {
+            headers.insert(AUTHORIZATION, value.clone());
+        }
+        headers
+    }
+}
+
+fn matches_domain(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches('.');
+    !domain.is_empty() && (host == domain || host.ends_with(&format!(".{domain}")))
+}
+
+/// Whether a cookie set for `cookie_path` goes with a request for `path`, as in
+/// RFC 6265: the same path or one below it.
+fn matches_path(path: &str, cookie_path: &str) -> bool {
+    let Some(rest) = path.strip_prefix(cookie_path) else {
+        return false;
+    };
+    rest.is_empty() || cookie_path.ends_with('/') || rest.starts_with('/')
+}
+
+fn attributes(domain: &str) -> HashMap<&str, &str> {
+    HashMap::from([("application", Ytdlp::APP_ID), ("domain", domain)])
+}
+
+pub async fn store_password(domain: String, password: String) -> Result<(), String> {
+    let keyring = oo7::Keyring::new()
+        .await
+        .map_err(|err| format!("failed to open the keyring: {err}"))?;
+    keyring
+        .create_item(
+            &format!("yt-dlp login for {domain}"),
+            &attributes(&domain),
+            password,
+            true,
+        )
+        .await
+        .map_err(|err| format!("failed to store the password: {err}"))
+}
+
+pub async fn forget_password(domain: String) {
+    if let Ok(keyring) = oo7::Keyring::new().await {
+        let _ = keyring.delete(&attributes(&domain)).await;
+    }
+}
+
+async fn password(domain: &str) -> Option<String> {
+    let keyring = oo7::Keyring::new().await.ok()?;
+    let items = keyring.search_items(&attributes(domain)).await.ok()?;
+    let secret = items.first()?.secret().await.ok()?;
+    String::from_utf8(secret.to_vec()).ok()
+}
+
+/// Cookies from a `cookies.txt` file.
+async fn cookies(file: &Path) -> Vec<Cookie> {
+    let contents = tokio::fs::read_to_string(file).await.unwrap_or_default();
+    parse_cookies(&contents)
+}
+
+fn parse_cookies(contents: &str) -> Vec<Cookie> {
+    contents
+        .lines()
+        .map(|line| line.strip_prefix("#HttpOnly_").unwrap_or(line))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, _, path, secure, expires, name, value] = fields[..] else {
+                return None;
+            };
+            Some(Cookie {
+                domain: domain.to_owned(),
+                path: path.to_owned(),
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                expires: expires.parse().ok()?,
+                pair: format!("{name}={value}"),
+            })
+        })
+        .collect()
+}
+
+/// Quotes a value for a yt-dlp config file, which is split like a shell command.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r#"'"'"'"#))
+}
+
+/// Writes the login to a yt-dlp config file only the user can read.
+fn config_file(username: &str, password: &str) -> Result<TempPath, String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("yt-dlp-login")
+        .suffix(".conf")
+        .tempfile()
+        .map_err(|err| format!("failed to create the login file: {err}"))?;
+    writeln!(
+        file,
+        "--username {}\n--password {}",
+        quote(username),
+        quote(password)
+    )
+    .map_err(|err| format!("failed to write the login file: {err}"))?;
+    Ok(file.into_temp_path())
+}
+
+impl Auth {
+    /// Collects the cookies and credentials that apply to `url`.
+    pub async fn login(&self, url: &str) -> Login {
+        let mut login = Login::default();
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+            .unwrap_or_default();
+
+        if !self.cookies_file.trim().is_empty() {
+            let cookies_file = folders::expand(&self.cookies_file);
+            login.args.extend([
+                String::from("--cookies"),
+                cookies_file.to_string_lossy().into_owned(),
+            ]);
+            login.cookies = cookies(&cookies_file).await;
+        }
+        if self.netrc {
+            login.args.push(String::from("--netrc"));
+        }
+
+        let Some(credential) = self
+            .credentials
+            .iter()
+            .find(|credential| matches_domain(&host, &credential.domain))
+        else {
+            return login;
+        };
+        let Some(password) = password(&credential.domain).await else {
+            return login;
+        };
+        let basic = STANDARD.encode(format!("{}:{password}", credential.username));
+        if let Ok(mut value) = HeaderValue::from_str(&format!("Basic {basic}")) {
+            value.set_sensitive(true);
+            login.basic = Some((credential.domain.clone(), value));
+        }
+        match config_file(&credential.username, &password) {
+            Ok(config) => {
+                login.args.extend([
+                    String::from("--config-locations"),
+                    config.to_string_lossy().into_owned(),
+                ]);
+                login.config = Some(Arc::new(config));
+            }
+            Err(err) => eprintln!("{err}"),
+        }
+        login
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    fn cookie(domain: &str, pair: &str) -> Cookie {
+        Cookie {
+            domain: domain.to_owned(),
+            path: String::from("/"),
+            pair: pair.to_owned(),
+            ..Cookie::default()
+        }
+    }
+
+    fn basic() -> Option<(String, HeaderValue)> {
+        Some((
+            String::from("example.com"),
+            HeaderValue::from_static("Basic dXNlcjpwYXNz"),
+        ))
+    }
+
+    #[test]
+    fn headers_only_go_to_their_domains() {
+        let login = Login {
+            cookies: vec![
+                cookie(".example.com", "session=1"),
+                cookie("www.example.com", "pref=2"),
+                cookie(".other.org", "tracker=3"),
+            ],
+            basic: basic(),
+            ..Login::default()
+        };
+
+        let headers = login.headers("https://www.example.com/watch?v=1");
+        assert_eq!(headers[COOKIE], "session=1; pref=2");
+        assert_eq!(headers[AUTHORIZATION], "Basic dXNlcjpwYXNz");
+
+        let headers = login.headers("https://media.example.com/segment.ts");
+        assert_eq!(headers[COOKIE], "session=1");
+        assert!(headers.contains_key(AUTHORIZATION));
+
+        assert!(
+            login
+                .headers("https://cdn.example.net/segment.ts")
+                .is_empty()
+        );
+        assert!(login.headers("https://notexample.com/").is_empty());
+    }
+
+    #[test]
+    fn logins_are_only_sent_over_https() {
+        let login = Login {
+            basic: basic(),
+            ..Login::default()
+        };
+        assert!(
+            login
+                .headers("https://example.com/")
+                .contains_key(AUTHORIZATION)
+        );
+        assert!(login.headers("http://example.com/").is_empty());
+    }
+
+    #[test]
+    fn cookies_keep_to_their_path_scheme_and_lifetime() {
+        let login = Login {
+            cookies: vec![
+                Cookie {
+                    path: String::from("/videos"),
+                    ..cookie("example.com", "path=1")
+                },
+                Cookie {
+                    secure: true,
+                    ..cookie("example.com", "secure=2")
+                },
+                Cookie {
+                    expires: 1,
+                    ..cookie("example.com", "expired=3")
+                },
+                Cookie {
+                    expires: u64::MAX,
+                    ..cookie("example.com", "lasting=4")
+                },
+            ],
+            ..Login::default()
+        };
+        assert_eq!(
+            login.headers("https://example.com/videos/1")[COOKIE],
+            "path=1; secure=2; lasting=4"
+        );
+        assert_eq!(
+            login.headers("https://example.com/videos")[COOKIE],
+            "path=1; secure=2; lasting=4"
+        );
+        assert_eq!(
+            login.headers("https://example.com/videoshop")[COOKIE],
+            "secure=2; lasting=4"
+        );
+        assert_eq!(
+            login.headers("http://example.com/videos/1")[COOKIE],
+            "path=1; lasting=4"
+        );
+    }
+
+    #[test]
+    fn cookie_files_are_parsed() {
+        let contents = "# Netscape HTTP Cookie File\n\
+            .example.com\tTRUE\t/\tTRUE\t1700000000\tsession\t1\n\
+            #HttpOnly_www.example.com\tFALSE\t/watch\tFALSE\t0\tpref\ta=b\n\
+            \n\
+            broken\tline\n";
+        assert_eq!(
+            parse_cookies(contents),
+            [
+                Cookie {
+                    domain: String::from(".example.com"),
+                    path: String::from("/"),
+                    secure: true,
+                    expires: 1_700_000_000,
+                    pair: String::from("session=1"),
+                },
+                Cookie {
+                    domain: String::from("www.example.com"),
+                    path: String::from("/watch"),
+                    secure: false,
+                    expires: 0,
+                    pair: String::from("pref=a=b"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn logins_stay_off_the_command_line() {
+        let config = config_file("user", "it's a \"secret\" # really").unwrap();
+        let contents = std::fs::read_to_string(&config).unwrap();
+        assert_eq!(
+            contents,
+            "--username 'user'\n--password 'it'\"'\"'s a \"secret\" # really'\n"
+        );
+        let mode = std::fs::metadata(&config).unwrap().permissions().mode();
+        assert_eq!(mode & 0o077, 0);
+    }
+}
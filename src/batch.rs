@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Lists of URLs, typed into the popup or imported from a text file.
+
+use crate::urls;
+
+/// A URL list split into what can be queued and what can't.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Batch {
+    /// New web URLs, in list order.
+    pub urls: Vec<String>,
+    /// Lines that aren't web URLs, by line number.
+    pub invalid: Vec<(usize, String)>,
+    /// URLs left out for being listed twice or already queued.
+    pub duplicates: usize,
+}
+
+/// Reads one URL per line, ignoring blank lines and `#` or `;` comments as in
+/// yt-dlp's batch files. URLs are cleaned up as typed ones are, and those in
+/// `queued` count as duplicates.
+pub fn parse(text: &str, queued: &[String]) -> Batch {
+    let mut batch = Batch::default();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+        let Ok(url) = urls::normalize(line) else {
+            batch.invalid.push((i + 1, line.to_owned()));
+            continue;
+        };
+        if batch.urls.contains(&url) || queued.contains(&url) {
+            batch.duplicates += 1;
+        } else {
+            batch.urls.push(url);
+        }
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let list =
+            "# favourites\n\n  https://example.com/a  \n; later\n\t\nhttps://example.com/b\n";
+        let batch = parse(list, &[]);
+        assert_eq!(
+            batch.urls,
+            ["https://example.com/a", "https://example.com/b"]
+        );
+        assert!(batch.invalid.is_empty());
+        assert_eq!(batch.duplicates, 0);
+    }
+
+    #[test]
+    fn invalid_lines_keep_their_line_number() {
+        let batch = parse("https://example.com/a\nnot a url\nftp://example.com/b", &[]);
+        assert_eq!(batch.urls, ["https://example.com/a"]);
+        assert_eq!(
+            batch.invalid,
+            [
+                (2, String::from("not a url")),
+                (3, String::from("ftp://example.com/b"))
+            ]
+        );
+    }
+
+    #[test]
+    fn urls_are_cleaned_up_like_typed_ones() {
+        let batch = parse(
+            "example.com/a?utm_source=feed\nhttps://youtu.be/abc?si=x",
+            &[],
+        );
+        assert_eq!(
+            batch.urls,
+            [
+                "https://example.com/a",
+                "https://www.youtube.com/watch?v=abc"
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicates_are_counted_once_cleaned_up() {
+        let queued = [String::from("https://example.com/queued")];
+        let list =
+            "https://example.com/a\nhttps://example.com/a?fbclid=1\nhttps://example.com/queued";
+        let batch = parse(list, &queued);
+        assert_eq!(batch.urls, ["https://example.com/a"]);
+        assert_eq!(batch.duplicates, 2);
+    }
+}
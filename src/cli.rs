@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use cosmic::Application;
+
+use crate::applet::Ytdlp;
+use crate::config::Config;
+use crate::download::{self, DownloadService, Event, YtdlpBackend};
+use crate::folders::{self, Problem};
+use crate::formats::DownloadOptions;
+use crate::jobs::JobState;
+use crate::split::Split;
+use crate::{fetcher, fl};
+
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Download URLs without starting the applet
+    Download(DownloadArgs),
+    /// Import yt-dlp and ffmpeg from a local directory or tar archive
+    Import {
+        /// Directory or archive containing the executables
+        path: PathBuf,
+    },
+}
+
+#[derive(Args)]
+pub struct DownloadArgs {
+    /// URLs to download, one after the other
+    #[arg(required = true)]
+    urls: Vec<String>,
+    /// Preset to start from, by name
+    #[arg(short, long)]
+    preset: Option<String>,
+    /// Download type, defaults to video
+    #[arg(short, long = "type", value_parser = ["video", "audio"])]
+    kind: Option<String>,
+    /// Quality, as shown in the applet (e.g. 1080p, 128kbps, highest)
+    #[arg(short, long)]
+    quality: Option<String>,
+    /// Codec, as shown in the applet (e.g. AV1, Opus, any)
+    #[arg(short, long)]
+    codec: Option<String>,
+    /// Output folder, defaults to the user's videos or music folder
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// HTTP, HTTPS or SOCKS proxy, overriding the applet's setting
+    #[arg(long)]
+    proxy: Option<String>,
+    /// Bandwidth limit in bytes per second (e.g. 500K, 2M), overriding the applet's setting
+    #[arg(long)]
+    limit_rate: Option<String>,
+    /// Only download from this time on (seconds, MM:SS or HH:MM:SS)
+    #[arg(long)]
+    start: Option<String>,
+    /// Only download up to this time (seconds, MM:SS or HH:MM:SS)
+    #[arg(long)]
+    end: Option<String>,
+    /// Split into one file per chapter, named by the applet's chapter template
+    #[arg(long)]
+    split_chapters: bool,
+    /// Download even if a URL is in the download archive
+    #[arg(long)]
+    ignore_archive: bool,
+}
+
+/// Runs a command to completion, returning the process exit code.
+pub async fn run(command: Command) -> i32 {
+    let args = match command {
+        Command::Download(args) => args,
+        Command::Import { path } => {
+            return match fetcher::import(&path).await {
+                Ok(binaries) => {
+                    if !binaries.unverified.is_empty() {
+                        let names = binaries.unverified.join(", ");
+                        eprintln!("{}", fl!("import-unverified", binaries = names));
+                    }
+                    0
+                }
+                Err(err) => {
+                    eprintln!("{}: {err}", fl!("yt-dlp-failed"));
+                    1
+                }
+            };
+        }
+    };
+
+    let (_, config) = Config::load(Ytdlp::APP_ID);
+    let mut options = DownloadOptions {
+        video: true,
+        network: config.network.clone(),
+        auth: config.auth.clone(),
+        sponsorblock: config.sponsorblock.clone(),
+        folder_rules: config.folder_rules.clone(),
+        ..DownloadOptions::default()
+    };
+    let mut default_folder = true;
+    if let Some(name) = &args.preset {
+        let Some(preset) = config.preset(name) else {
+            eprintln!("unknown preset '{name}'");
+            return 2;
+        };
+        if let Err(err) = preset.apply(&mut options) {
+            eprintln!("preset '{name}': {err}");
+            return 2;
+        }
+        default_folder = preset.folder.trim().is_empty();
+    }
+    let overrides = [
+        ("type", args.kind.as_ref()),
+        ("quality", args.quality.as_ref()),
+        ("codec", args.codec.as_ref()),
+        ("proxy", args.proxy.as_ref()),
+        ("rate-limit", args.limit_rate.as_ref()),
+        ("start", args.start.as_ref()),
+        ("end", args.end.as_ref()),
+    ];
+    for (key, value) in overrides {
+        let Some(value) = value else {
+            continue;
+        };
+        let key = if matches!(key, "quality" | "codec") {
+            let kind = if options.video { "video" } else { "audio" };
+            format!("{kind}-{key}")
+        } else {
+            key.to_owned()
+        };
+        if let Err(err) = options.set(&key, value) {
+            eprintln!("{err}");
+            return 2;
+        }
+    }
+    options.ignore_archive = args.ignore_archive;
+    options.split = Split {
+        enabled: args.split_chapters,
+        ..config.split.clone()
+    };
+    if let Some(output) = args.output {
+        options.output_dir = output;
+    } else if default_folder {
+        options.output_dir = PathBuf::from(download::default_folder(options.video));
+    }
+    // A missing folder is created, as yt-dlp does with its output paths
+    let problem = match folders::check(&options.output_dir) {
+        Err(Problem::Missing) => std::fs::create_dir_all(&options.output_dir)
+            .map_err(|err| format!("failed to create {}: {err}", options.output_dir.display())),
+        res => res.map_err(|problem| format!("{}: {problem}", options.output_dir.display())),
+    };
+    if let Err(err) = problem {
+        eprintln!("{err}");
+        return 2;
+    }
+
+    let binaries = match fetcher::binaries(&config, |_| {}).await {
+        Ok(binaries) => {
+            for file in &binaries.quarantined {
+                eprintln!("{}", fl!("quarantined", file = file.to_string_lossy()));
+            }
+            binaries
+        }
+        Err(err) => {
+            eprintln!("{}: {err}", fl!("yt-dlp-failed"));
+            return 1;
+        }
+    };
+    let downloads = DownloadService::new(YtdlpBackend::new(binaries));
+    let mut events = downloads.subscribe();
+    tokio::spawn(async move {
+        while let Ok(Event { job, .. }) = events.recv().await {
+            if job.state == JobState::Downloading {
+                eprintln!("{}", fl!("start-download", title = job.title));
+            }
+            // Whatever went wrong without failing the download
+            if let (JobState::Done, Some(note)) = (job.state, &job.error) {
+                eprintln!("{note}");
+            }
+        }
+    });
+
+    let mut failed = false;
+    for url in args.urls {
+        let id = downloads.queue(url.clone());
+        match downloads.run(id, &url, &options).await {
+            Ok(title) => eprintln!("{}", fl!("finished-download", title = title)),
+            Err(err @ download::Error::Archived(_)) => eprintln!("{err}"),
+            Err(err) => {
+                eprintln!("{err}");
+                failed = true;
+            }
+        }
+    }
+    i32::from(failed)
+}
@@ -0,0 +1,290 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Downloading only part of a video.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Time range to download, in seconds. Open ends reach the start or end of the video.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clip {
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+}
+
+impl Clip {
+    pub fn is_set(&self) -> bool {
+        self.start.is_some() || self.end.is_some()
+    }
+
+    /// Whether the range is empty or reversed.
+    pub fn is_empty(&self) -> bool {
+        matches!((self.start, self.end), (Some(start), Some(end)) if end <= start)
+    }
+
+    /// Arguments that make yt-dlp download only this range.
+    pub fn ytdlp_args(&self) -> Vec<String> {
+        if !self.is_set() {
+            return vec![];
+        }
+        let start = self.start.unwrap_or_default();
+        let end = self.end.map_or(String::from("inf"), |end| end.to_string());
+        vec![
+            String::from("--download-sections"),
+            format!("*{start}-{end}"),
+        ]
+    }
+
+    /// Picks the segments of a playlist that cover the range, from their durations.
+    /// Returns the indices of the first and last segment, and the range relative to
+    /// the start of the first one.
+    pub fn segments(&self, durations: &[f64]) -> Option<(usize, usize, Clip)> {
+        let start = to_f64(self.start.unwrap_or_default());
+        let end = self.end.map_or(f64::INFINITY, to_f64);
+        let mut position = 0.0;
+        let mut first = None;
+        let mut last = None;
+        for (i, duration) in durations.iter().enumerate() {
+            let segment_end = position + duration;
+            if segment_end > start && position < end {
+                first.get_or_insert((i, position));
+                last = Some(i);
+            }
+            position = segment_end;
+        }
+        let ((first, offset), last) = (first?, last?);
+        let relative = Clip {
+            start: Some(from_f64((start - offset).floor())?),
+            end: self
+                .end
+                .and_then(|end| from_f64((to_f64(end) - offset).ceil())),
+        };
+        Some((first, last, relative))
+    }
+
+    /// The part of `start..end` inside the range, relative to its start.
+    pub fn relative(&self, start: f64, end: f64) -> Option<(f64, f64)> {
+        let clip_start = to_f64(self.start.unwrap_or_default());
+        let clip_end = self.end.map_or(f64::INFINITY, to_f64);
+        (end > clip_start && start < clip_end).then(|| {
+            (
+                start.max(clip_start) - clip_start,
+                end.min(clip_end) - clip_start,
+            )
+        })
+    }
+
+    /// ffmpeg output options that cut the range out of the input.
+    pub fn ffmpeg_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(start) = self.start.filter(|start| *start > 0) {
+            args.extend([String::from("-ss"), start.to_string()]);
+        }
+        if let Some(end) = self.end {
+            args.extend([String::from("-to"), end.to_string()]);
+        }
+        args
+    }
+}
+
+/// A chapter from the video's metadata.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Chapter {
+    pub title: String,
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+impl Chapter {
+    /// The chapter as a range, widened to whole seconds.
+    pub fn clip(&self) -> Clip {
+        Clip {
+            start: from_f64(self.start_time.floor()),
+            end: from_f64(self.end_time.ceil()),
+        }
+    }
+}
+
+impl Display for Chapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let start = format_timestamp(from_f64(self.start_time.floor()).unwrap_or_default());
+        write!(f, "{start} {}", self.title)
+    }
+}
+
+/// Seconds as a float, clamped to `u32::MAX` (over a century) so none are lost.
+fn to_f64(seconds: u64) -> f64 {
+    f64::from(u32::try_from(seconds).unwrap_or(u32::MAX))
+}
+
+/// Whole seconds of a time, `None` if it's negative, not a number or past `u32::MAX`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn from_f64(seconds: f64) -> Option<u64> {
+    // Only the fraction is dropped, the range was checked
+    (0.0..=f64::from(u32::MAX))
+        .contains(&seconds)
+        .then(|| seconds as u64)
+}
+
+/// Parses `SS`, `MM:SS` or `HH:MM:SS` into seconds.
+pub fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let parts: Vec<&str> = timestamp.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    parts.iter().enumerate().try_fold(0, |seconds, (i, part)| {
+        let value: u64 = part.parse().ok()?;
+        // Only the leading part may exceed 59, e.g. `90:00`
+        (i == 0 || value < 60).then_some(seconds * 60 + value)
+    })
+}
+
+pub fn format_timestamp(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(start: Option<u64>, end: Option<u64>) -> Clip {
+        Clip { start, end }
+    }
+
+    #[test]
+    fn timestamps_are_parsed() {
+        assert_eq!(parse_timestamp("42"), Some(42));
+        assert_eq!(parse_timestamp(" 1:05 "), Some(65));
+        assert_eq!(parse_timestamp("1:02:03"), Some(3723));
+        assert_eq!(parse_timestamp("90:00"), Some(5400));
+        assert_eq!(parse_timestamp("0:00:00"), Some(0));
+    }
+
+    #[test]
+    fn malformed_timestamps_are_rejected() {
+        for timestamp in [
+            "", "1:60", "1:00:60", "1:2:3:4", "-5", "1.5", "a:00", "1::00",
+        ] {
+            assert_eq!(parse_timestamp(timestamp), None, "{timestamp}");
+        }
+    }
+
+    #[test]
+    fn timestamps_are_formatted() {
+        assert_eq!(format_timestamp(5), "0:05");
+        assert_eq!(format_timestamp(3599), "59:59");
+        assert_eq!(format_timestamp(3723), "1:02:03");
+        for seconds in [0, 59, 61, 3600, 86399] {
+            assert_eq!(
+                parse_timestamp(&format_timestamp(seconds)),
+                Some(seconds),
+                "{seconds}"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_and_reversed_clips() {
+        assert!(!clip(None, None).is_set());
+        assert!(clip(Some(10), Some(10)).is_empty());
+        assert!(clip(Some(10), Some(5)).is_empty());
+        assert!(!clip(Some(10), None).is_empty());
+        assert!(!clip(None, Some(5)).is_empty());
+    }
+
+    #[test]
+    fn segments_cover_the_clip() {
+        let durations = [4.0, 4.0, 4.0];
+        assert_eq!(
+            clip(Some(5), None).segments(&durations),
+            Some((1, 2, clip(Some(1), None)))
+        );
+        assert_eq!(
+            clip(Some(5), Some(7)).segments(&durations),
+            Some((1, 1, clip(Some(1), Some(3))))
+        );
+        assert_eq!(
+            clip(None, Some(9)).segments(&durations),
+            Some((0, 2, clip(Some(0), Some(9))))
+        );
+        // A segment ending where the clip starts isn't needed
+        assert_eq!(
+            clip(Some(4), Some(8)).segments(&durations),
+            Some((1, 1, clip(Some(0), Some(4))))
+        );
+    }
+
+    #[test]
+    fn segments_round_the_relative_end_up() {
+        let durations = [2.5, 2.5, 2.5];
+        assert_eq!(
+            clip(Some(3), Some(6)).segments(&durations),
+            Some((1, 2, clip(Some(0), Some(4))))
+        );
+    }
+
+    #[test]
+    fn clips_past_the_end_have_no_segments() {
+        assert_eq!(clip(Some(20), None).segments(&[4.0, 4.0]), None);
+        assert_eq!(clip(None, None).segments(&[]), None);
+    }
+
+    #[test]
+    fn ranges_are_made_relative_to_the_clip() {
+        let clip = clip(Some(10), Some(20));
+        assert_eq!(clip.relative(0.0, 15.0), Some((0.0, 5.0)));
+        assert_eq!(clip.relative(12.0, 18.0), Some((2.0, 8.0)));
+        assert_eq!(clip.relative(15.0, 30.0), Some((5.0, 10.0)));
+        assert_eq!(clip.relative(0.0, 10.0), None);
+        assert_eq!(clip.relative(20.0, 30.0), None);
+        assert_eq!(
+            Clip::default().relative(3.0, f64::INFINITY),
+            Some((3.0, f64::INFINITY))
+        );
+    }
+
+    #[test]
+    fn chapters_widen_to_whole_seconds() {
+        let chapter = Chapter {
+            title: String::from("Intro"),
+            start_time: 61.7,
+            end_time: 90.2,
+        };
+        assert_eq!(chapter.clip(), clip(Some(61), Some(91)));
+        assert_eq!(chapter.to_string(), "1:01 Intro");
+    }
+
+    #[test]
+    fn out_of_range_times_are_open() {
+        let chapter = Chapter {
+            title: String::new(),
+            start_time: -1.0,
+            end_time: f64::NAN,
+        };
+        assert_eq!(chapter.clip(), clip(None, None));
+    }
+
+    #[test]
+    fn arguments_cut_the_clip() {
+        assert!(Clip::default().ytdlp_args().is_empty());
+        assert_eq!(
+            clip(Some(5), None).ytdlp_args(),
+            ["--download-sections", "*5-inf"]
+        );
+        assert_eq!(
+            clip(None, Some(9)).ytdlp_args(),
+            ["--download-sections", "*0-9"]
+        );
+        assert_eq!(clip(Some(0), Some(9)).ffmpeg_args(), ["-to", "9"]);
+        assert_eq!(
+            clip(Some(5), Some(9)).ffmpeg_args(),
+            ["-ss", "5", "-to", "9"]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::borrow::Cow;
+use std::io::Read;
+
+use cosmic::iced::clipboard::mime::AllowedMimeTypes;
+use url::Url;
+use wl_clipboard_rs::paste::{ClipboardType, MimeType, Seat, get_contents};
+
+use crate::batch;
+use crate::config::Config;
+use crate::urls;
+
+/// Reads the current clipboard text through the Wayland data-control protocol.
+pub async fn read() -> Option<String> {
+    tokio::task::spawn_blocking(|| {
+        let (mut pipe, _) =
+            get_contents(ClipboardType::Regular, Seat::Unspecified, MimeType::Text).ok()?;
+        let mut contents = String::new();
+        pipe.read_to_string(&mut contents).ok()?;
+        Some(contents.trim().to_owned())
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Web URLs dragged onto the popup, e.g. links or tabs from a browser.
+#[derive(Debug, Clone)]
+pub struct DroppedUrls(pub Vec<String>);
+
+impl AllowedMimeTypes for DroppedUrls {
+    fn allowed() -> Cow<'static, [String]> {
+        Cow::Owned(vec![
+            String::from("text/uri-list"),
+            String::from("text/x-moz-url"),
+            String::from("text/plain;charset=utf-8"),
+            String::from("text/plain"),
+        ])
+    }
+}
+
+impl TryFrom<(Vec<u8>, String)> for DroppedUrls {
+    type Error = String;
+
+    fn try_from((data, mime): (Vec<u8>, String)) -> Result<Self, Self::Error> {
+        // Firefox sends text/x-moz-url as UTF-16, with the title on the line after the URL
+        let text = if mime == "text/x-moz-url" {
+            let units: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            let text = String::from_utf16_lossy(&units);
+            text.lines().step_by(2).collect::<Vec<_>>().join("\n")
+        } else {
+            String::from_utf8_lossy(&data).into_owned()
+        };
+        // `text/uri-list` comments start with `#`, as in batch files
+        let urls = batch::parse(&text, &[]).urls;
+        if urls.is_empty() {
+            Err(format!("no web URLs in dropped {mime}"))
+        } else {
+            Ok(Self(urls))
+        }
+    }
+}
+
+/// Returns the clipboard text if it is a web URL accepted by the domain lists.
+pub fn candidate(text: &str, config: &Config) -> Option<String> {
+    let url = Url::parse(text).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    config
+        .domain_allowed(url.host_str()?)
+        .then(|| urls::clean(url).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dropped(data: &[u8], mime: &str) -> Result<Vec<String>, String> {
+        DroppedUrls::try_from((data.to_vec(), mime.to_owned())).map(|urls| urls.0)
+    }
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn uri_lists_skip_comments() {
+        let list = "# dragged from a browser\r\nhttps://example.com/a\r\nhttps://example.com/b\r\n";
+        assert_eq!(
+            dropped(list.as_bytes(), "text/uri-list").unwrap(),
+            ["https://example.com/a", "https://example.com/b"]
+        );
+    }
+
+    #[test]
+    fn moz_urls_skip_their_titles() {
+        let data = utf16("https://example.com/a\nexample.org\nhttps://example.com/b\nB");
+        assert_eq!(
+            dropped(&data, "text/x-moz-url").unwrap(),
+            ["https://example.com/a", "https://example.com/b"]
+        );
+    }
+
+    #[test]
+    fn drops_without_urls_are_refused() {
+        assert!(dropped(b"just some text", "text/plain").is_err());
+        assert!(dropped(b"ftp://example.com/file", "text/plain").is_err());
+    }
+}
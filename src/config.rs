@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fmt::Display;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use cosmic::cosmic_config::{self, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::Auth;
+use crate::fl;
+use crate::network::Network;
+use crate::power::PowerPolicy;
+use crate::presets::Preset;
+use crate::rules::FolderRule;
+use crate::schedule::{ScheduledJob, Window};
+use crate::split::Split;
+use crate::sponsorblock::SponsorBlock;
+use crate::subscriptions::Feed;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateInterval {
+    Never,
+    #[default]
+    Daily,
+    Weekly,
+}
+
+impl UpdateInterval {
+    pub const ALL: [Self; 3] = [Self::Never, Self::Daily, Self::Weekly];
+
+    pub fn duration(self) -> Option<Duration> {
+        match self {
+            UpdateInterval::Never => None,
+            UpdateInterval::Daily => Some(Duration::from_secs(24 * 60 * 60)),
+            UpdateInterval::Weekly => Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        }
+    }
+}
+
+impl Display for UpdateInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateInterval::Never => write!(f, "{}", fl!("never")),
+            UpdateInterval::Daily => write!(f, "{}", fl!("daily")),
+            UpdateInterval::Weekly => write!(f, "{}", fl!("weekly")),
+        }
+    }
+}
+
+/// Where the yt-dlp and ffmpeg executables come from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinarySource {
+    #[default]
+    Bundled,
+    System,
+    Custom,
+}
+
+impl BinarySource {
+    pub const ALL: [Self; 3] = [Self::Bundled, Self::System, Self::Custom];
+}
+
+impl Display for BinarySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinarySource::Bundled => write!(f, "{}", fl!("binaries-bundled")),
+            BinarySource::System => write!(f, "{}", fl!("binaries-system")),
+            BinarySource::Custom => write!(f, "{}", fl!("binaries-custom")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct Config {
+    pub clipboard_watch: bool,
+    pub domain_allow: Vec<String>,
+    pub domain_deny: Vec<String>,
+    pub update_interval: UpdateInterval,
+    /// Unix time of the last yt-dlp update check.
+    pub last_update_check: u64,
+    pub binary_source: BinarySource,
+    pub custom_yt_dlp: String,
+    pub custom_ffmpeg: String,
+    /// Directory or archive to provision missing binaries from, instead of downloading them.
+    pub import_path: String,
+    pub network: Network,
+    pub auth: Auth,
+    pub presets: Vec<Preset>,
+    /// Name of the last used preset, empty for none.
+    pub last_preset: String,
+    pub feeds: Vec<Feed>,
+    pub feed_interval: UpdateInterval,
+    /// Unix time of the last subscriptions poll.
+    pub last_feed_check: u64,
+    pub window: Window,
+    /// Downloads waiting to start, kept across restarts.
+    pub scheduled: Vec<ScheduledJob>,
+    /// What to do with downloads on metered connections and on battery.
+    pub power: PowerPolicy,
+    /// Chapter splitting as last chosen, with its file name template and playlist option.
+    pub split: Split,
+    pub sponsorblock: SponsorBlock,
+    /// Output folders by domain, uploader or playlist, first match first.
+    pub folder_rules: Vec<FolderRule>,
+}
+
+impl Config {
+    pub fn load(app_id: &str) -> (Option<cosmic_config::Config>, Self) {
+        let Ok(handler) = cosmic_config::Config::new(app_id, Self::VERSION) else {
+            return (None, Self::default());
+        };
+        let config = Self::get_entry(&handler).unwrap_or_else(|(errs, config)| {
+            for err in errs {
+                eprintln!("error while loading config: {err}");
+            }
+            config
+        });
+        (Some(handler), config)
+    }
+
+    /// Checks a host against the allow and deny lists.
+    /// An empty allow list accepts every host that isn't denied.
+    pub fn domain_allowed(&self, host: &str) -> bool {
+        let matches = |domain: &String| {
+            let domain = domain.trim();
+            !domain.is_empty() && (host == domain || host.ends_with(&format!(".{domain}")))
+        };
+        if self.domain_deny.iter().any(matches) {
+            return false;
+        }
+        self.domain_allow.iter().all(|d| d.trim().is_empty())
+            || self.domain_allow.iter().any(matches)
+    }
+
+    /// Built-in presets followed by the user's own.
+    pub fn presets(&self) -> Vec<Preset> {
+        let mut presets = Preset::builtin();
+        presets.extend(self.presets.iter().cloned());
+        presets
+    }
+
+    pub fn preset(&self, name: &str) -> Option<Preset> {
+        self.presets()
+            .into_iter()
+            .find(|preset| preset.name == name)
+    }
+
+    /// Whether the periodic yt-dlp update check is due.
+    pub fn update_due(&self) -> bool {
+        due(self.update_interval, self.last_update_check)
+    }
+
+    /// Whether subscriptions should be polled.
+    pub fn feeds_due(&self) -> bool {
+        !self.feeds.is_empty() && due(self.feed_interval, self.last_feed_check)
+    }
+}
+
+fn due(interval: UpdateInterval, last: u64) -> bool {
+    interval
+        .duration()
+        .is_some_and(|interval| now().saturating_sub(last) >= interval.as_secs())
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+
+use cosmic::Application;
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{Subscription, stream};
+use tokio::sync::{broadcast, mpsc};
+use zbus::object_server::SignalEmitter;
+use zbus::{Connection, fdo, interface};
+
+use crate::applet::Ytdlp;
+use crate::download::{DownloadService, Event};
+use crate::formats::DownloadOptions;
+use crate::urls;
+
+pub const PATH: &str = "/dev/DBrox/CosmicYtdlp";
+
+/// A job queued over D-Bus, to be started with the applet's current selections.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub id: u32,
+    pub url: String,
+    pub options: HashMap<String, String>,
+}
+
+struct Service {
+    downloads: DownloadService,
+    requests: mpsc::UnboundedSender<Request>,
+}
+
+#[interface(name = "dev.DBrox.CosmicYtdlp1")]
+impl Service {
+    /// Queues a download using the applet's current selections, overridden by `options`.
+    /// Known options: type, video-quality, video-codec, audio-quality, audio-codec, folder,
+    /// proxy, rate-limit, ignore-archive, start, end, split-chapters. Malformed URLs,
+    /// unknown options and malformed values are rejected with `InvalidArgs`.
+    async fn enqueue(&self, url: String, options: HashMap<String, String>) -> fdo::Result<u32> {
+        // Validate before queueing, so callers get the error back
+        let url = urls::normalize(&url)
+            .map_err(|invalid| fdo::Error::InvalidArgs(invalid.to_string()))?;
+        let mut check = DownloadOptions::default();
+        for (key, value) in &options {
+            check.set(key, value).map_err(fdo::Error::InvalidArgs)?;
+        }
+        let id = self.downloads.queue(url.clone());
+        self.requests
+            .send(Request { id, url, options })
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+        Ok(id)
+    }
+
+    /// Cancels a waiting or running job, returning whether it was either.
+    async fn cancel(&self, id: u32) -> bool {
+        self.downloads.cancel(id)
+    }
+
+    /// Lists waiting and running jobs and the most recent finished ones, as
+    /// (id, url, title, state).
+    async fn list_jobs(&self) -> Vec<(u32, String, String, String)> {
+        self.downloads
+            .jobs()
+            .list()
+            .into_iter()
+            .map(|(id, job)| (id, job.url, job.title, job.state.to_string()))
+            .collect()
+    }
+
+    /// Emitted whenever a job changes state, with its state as `ListJobs` gives it.
+    #[zbus(signal)]
+    async fn job_changed(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        title: &str,
+        state: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// Serves the applet on the session bus, forwarding queued jobs to the applet.
+pub fn subscription(downloads: DownloadService) -> Subscription<Request> {
+    Subscription::run_with_id(
+        "dbus-service",
+        stream::channel(16, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let events = downloads.subscribe();
+            let service = Service {
+                downloads,
+                requests: tx,
+            };
+            let connection = async {
+                zbus::connection::Builder::session()?
+                    .name(Ytdlp::APP_ID)?
+                    .serve_at(PATH, service)?
+                    .build()
+                    .await
+            };
+            match connection.await {
+                Ok(connection) => {
+                    tokio::spawn(job_changes(connection, events));
+                    while let Some(request) = rx.recv().await {
+                        let _ = output.send(request).await;
+                    }
+                }
+                Err(err) => eprintln!("failed to start D-Bus service: {err}"),
+            }
+            std::future::pending::<()>().await;
+        }),
+    )
+}
+
+/// Emits the `JobChanged` signal for every job change.
+async fn job_changes(connection: Connection, mut events: broadcast::Receiver<Event>) {
+    let Ok(emitter) = SignalEmitter::new(&connection, PATH) else {
+        return;
+    };
+    loop {
+        match events.recv().await {
+            Ok(Event { id, job }) => {
+                let state = job.state.to_string();
+                let _ = Service::job_changed(&emitter, id, &job.title, &state).await;
+            }
+            Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
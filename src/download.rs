@@ -0,0 +1,815 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fmt::Display;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use tokio::sync::broadcast;
+use yt_dlp::model::format::Format;
+
+use crate::archive::Archive;
+use crate::auth::Login;
+use crate::clip::Chapter;
+use crate::fetcher::{self, Binaries, Segments};
+use crate::formats::DownloadOptions;
+use crate::jobs::{JobInfo, JobState, Jobs};
+use crate::sponsorblock::Segment;
+use crate::{fl, folders, rules, split};
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// Why the metadata couldn't be fetched.
+    Metadata(String),
+    MissingFormat,
+    Download {
+        title: String,
+        reason: String,
+    },
+    /// The item is in the download archive.
+    Archived(String),
+    /// The output folder doesn't have room for the file, in bytes.
+    NoSpace {
+        title: String,
+        needed: u64,
+        free: u64,
+    },
+}
+
+impl Error {
+    fn download(title: &str, reason: impl Into<String>) -> Self {
+        Error::Download {
+            title: title.to_owned(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Metadata(reason) => write!(f, "{}: {reason}", fl!("metadata-failed")),
+            Error::MissingFormat => write!(f, "{}", fl!("missing-format")),
+            Error::Download { title, reason } => {
+                write!(f, "{}: {reason}", fl!("download-failed", title = title))
+            }
+            Error::Archived(title) => write!(f, "{}", fl!("already-downloaded", title = title)),
+            Error::NoSpace {
+                title,
+                needed,
+                free,
+            } => write!(
+                f,
+                "{}",
+                fl!(
+                    "no-space",
+                    title = title,
+                    needed = folders::format_size(*needed),
+                    free = folders::format_size(*free)
+                )
+            ),
+        }
+    }
+}
+
+/// Default download folder for the given download type.
+pub fn default_folder(video: bool) -> String {
+    let (dir, fallback) = if video {
+        (xdg_user::videos(), "~/Videos")
+    } else {
+        (xdg_user::music(), "~/Music")
+    };
+    let dir = dir
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| folders::expand(fallback));
+    String::from(dir.to_string_lossy())
+}
+
+/// The steps of a download, one per job state.
+pub trait Backend: Send + Sync + 'static {
+    /// A picked format, with whatever is needed to download it.
+    type Selection: Send;
+    /// Downloaded data that may still need post-processing.
+    type Downloaded: Send;
+
+    /// Fetches metadata and picks a format, returning the title and the selection.
+    fn select(
+        &self,
+        url: &str,
+        options: &DownloadOptions,
+    ) -> impl Future<Output = Result<(String, Self::Selection), Error>> + Send;
+
+    /// Key identifying the selected item in the download archive, if it has one.
+    fn archive_key(&self, selection: &Self::Selection) -> Option<String>;
+
+    fn download(
+        &self,
+        url: &str,
+        title: &str,
+        selection: Self::Selection,
+        options: &DownloadOptions,
+    ) -> impl Future<Output = Result<Self::Downloaded, Error>> + Send;
+
+    /// Returns what went wrong without stopping the download, if anything.
+    fn post_process(
+        &self,
+        title: &str,
+        downloaded: Self::Downloaded,
+        options: &DownloadOptions,
+    ) -> impl Future<Output = Result<Option<String>, Error>> + Send;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct YtdlpBackend {
+    binaries: Arc<RwLock<Binaries>>,
+}
+
+impl YtdlpBackend {
+    pub fn new(binaries: Binaries) -> Self {
+        Self {
+            binaries: Arc::new(RwLock::new(binaries)),
+        }
+    }
+
+    pub fn binaries(&self) -> Binaries {
+        self.binaries.read().unwrap().clone()
+    }
+
+    /// Swaps the executables used by jobs started from now on.
+    pub fn set_binaries(&self, binaries: Binaries) {
+        *self.binaries.write().unwrap() = binaries;
+    }
+
+    /// Downloads the selected format, returning where it ended up.
+    async fn download_output(
+        &self,
+        url: &str,
+        title: &str,
+        format: Format,
+        login: Login,
+        options: &DownloadOptions,
+    ) -> Result<Output, String> {
+        let format_url = format.download_info.url.as_deref();
+        let no_url = || format!("format {} has no URL", format.format_id);
+        if format.is_manifest() {
+            // The playlist is wherever the format says, segments are relative to it
+            let (network, clip) = (&options.network, &options.clip);
+            let output_dir = &options.output_dir;
+            let url = format_url.ok_or_else(no_url)?;
+            return fetcher::manifest(url, output_dir, network, &login, clip)
+                .await
+                .map(Output::Segments);
+        }
+        if !options.extra_args.is_empty() || options.clip.is_set() {
+            let mut args = options.network.ytdlp_args();
+            args.extend(login.args);
+            args.extend(options.clip.ytdlp_args());
+            args.extend(options.extra_args.iter().cloned());
+            return fetcher::download_with_ytdlp(
+                &self.binaries(),
+                args,
+                &format.format_id,
+                &options.output_dir,
+                title,
+                url,
+            )
+            .await
+            .map(Output::File);
+        }
+        let extension = if options.video { "mp4" } else { "m4a" };
+        let file_name = format!("{title}.{extension}");
+        let file = options.output_dir.join(&file_name);
+        let headers = format_url.map(|url| login.headers(url)).unwrap_or_default();
+        if options.network.is_set() || !headers.is_empty() {
+            // yt-dlp-rs' own downloader can't be configured, so fetch the format directly
+            let network = &options.network;
+            network
+                .download(format_url.ok_or_else(no_url)?, &file, headers)
+                .await?;
+            return Ok(Output::File(file));
+        }
+        let mut fetcher = fetcher::with_output_dir(
+            &self.binaries(),
+            options.output_dir.clone(),
+            &options.network,
+        );
+        fetcher.args.extend(login.args);
+        fetcher
+            .download_format(&format, file_name)
+            .await
+            .map_err(|err| err.to_string())?;
+        Ok(Output::File(file))
+    }
+}
+
+pub struct Selected {
+    format: Format,
+    login: Login,
+    archive_key: Option<String>,
+    /// Chapters of the item, within the clip if one is set.
+    chapters: Vec<Chapter>,
+    /// SponsorBlock segments to remove or mark.
+    segments: Vec<Segment>,
+    /// Why SponsorBlock couldn't be asked for segments.
+    sponsorblock_error: Option<String>,
+    /// Folder picked by the folder rules.
+    output_dir: PathBuf,
+}
+
+/// Where a download ended up.
+pub enum Output {
+    File(PathBuf),
+    Segments(Segments),
+}
+
+pub struct Downloaded {
+    output: Output,
+    chapters: Vec<Chapter>,
+    segments: Vec<Segment>,
+    sponsorblock_error: Option<String>,
+    output_dir: PathBuf,
+}
+
+impl Backend for YtdlpBackend {
+    type Selection = Selected;
+    type Downloaded = Downloaded;
+
+    async fn select(
+        &self,
+        url: &str,
+        options: &DownloadOptions,
+    ) -> Result<(String, Selected), Error> {
+        let login = options.auth.login(url).await;
+        let binaries = self.binaries();
+        let info = fetcher::info(&binaries, &options.network, &login, url)
+            .await
+            .map_err(Error::Metadata)?;
+        let video = &info.video;
+        let mut title = stem(&video.filename).to_owned();
+        if !options.filename_template.is_empty() {
+            let filename = info.filename(&binaries, &options.filename_template).await;
+            title = filename.map_err(Error::Metadata)?;
+        }
+        let format = if options.video {
+            video.select_video_format(options.video_quality.into(), options.video_codec.into())
+        } else {
+            video.select_audio_format(options.audio_quality.into(), options.audio_codec.into())
+        };
+        let format = format.ok_or(Error::MissingFormat)?.clone();
+        // A clip doesn't stand for the whole item
+        let archive_key = if options.clip.is_set() {
+            None
+        } else {
+            info.archive_key()
+        };
+        let chapters = split::within(&info.chapters(), &options.clip);
+        // The download goes ahead unedited if SponsorBlock can't be reached
+        let segments = options
+            .sponsorblock
+            .segments(&options.network, url, &video.id, &options.clip)
+            .await;
+        let (segments, sponsorblock_error) = match segments {
+            Ok(segments) => (segments, None),
+            Err(err) => (vec![], Some(err)),
+        };
+        let mut output_dir = options.output_dir.clone();
+        if !options.folder_rules.is_empty() {
+            output_dir = rules::folder(
+                &options.folder_rules,
+                options.video,
+                &info.metadata(url),
+                &options.output_dir,
+            );
+            tokio::fs::create_dir_all(&output_dir)
+                .await
+                .map_err(|err| {
+                    let reason = format!("failed to create {}: {err}", output_dir.display());
+                    Error::download(&title, reason)
+                })?;
+        }
+        Ok((
+            title,
+            Selected {
+                format,
+                login,
+                archive_key,
+                chapters,
+                segments,
+                sponsorblock_error,
+                output_dir,
+            },
+        ))
+    }
+
+    fn archive_key(&self, selection: &Selected) -> Option<String> {
+        selection.archive_key.clone()
+    }
+
+    async fn download(
+        &self,
+        url: &str,
+        title: &str,
+        Selected {
+            format,
+            login,
+            chapters,
+            segments,
+            sponsorblock_error,
+            output_dir,
+            ..
+        }: Selected,
+        options: &DownloadOptions,
+    ) -> Result<Downloaded, Error> {
+        // Clips are only part of the reported size, so they're left to fail on their own
+        if !options.clip.is_set() {
+            let size = format
+                .file_info
+                .filesize
+                .or(format.file_info.filesize_approx);
+            let needed = size.and_then(|size| u64::try_from(size).ok());
+            let short = needed
+                .zip(folders::free_space(&output_dir))
+                .filter(|(needed, free)| needed > free);
+            if let Some((needed, free)) = short {
+                return Err(Error::NoSpace {
+                    title: title.to_owned(),
+                    needed,
+                    free,
+                });
+            }
+        }
+        let options = &DownloadOptions {
+            output_dir: output_dir.clone(),
+            ..options.clone()
+        };
+        self.download_output(url, title, format, login, options)
+            .await
+            .map(|output| Downloaded {
+                output,
+                chapters,
+                segments,
+                sponsorblock_error,
+                output_dir,
+            })
+            .map_err(|reason| Error::download(title, reason))
+    }
+
+    async fn post_process(
+        &self,
+        title: &str,
+        Downloaded {
+            output,
+            mut chapters,
+            segments,
+            sponsorblock_error,
+            output_dir,
+        }: Downloaded,
+        options: &DownloadOptions,
+    ) -> Result<Option<String>, Error> {
+        let ffmpeg = self.binaries().ffmpeg;
+        let file = match output {
+            Output::File(file) => file,
+            Output::Segments(segments) => {
+                fetcher::concat(segments, &output_dir, ffmpeg.clone(), title, options.video)
+                    .await
+                    .map_err(|reason| Error::download(title, reason))?
+            }
+        };
+        if !segments.is_empty() {
+            let sponsorblock = &options.sponsorblock;
+            sponsorblock
+                .apply(&ffmpeg, &file, &chapters, &segments)
+                .await
+                .map_err(|reason| Error::download(title, reason))?;
+            chapters = sponsorblock.remap(&chapters, &segments);
+        }
+        // Files without chapters stay whole
+        if !options.split.enabled || chapters.is_empty() {
+            return Ok(sponsorblock_error);
+        }
+        split::split(&ffmpeg, &file, &chapters, &options.split, !options.video)
+            .await
+            .map(|_| sponsorblock_error)
+            .map_err(|reason| Error::download(title, reason))
+    }
+}
+
+/// File name yt-dlp would use without its extension, the whole name if it has none.
+fn stem(filename: &str) -> &str {
+    filename.rsplit_once('.').map_or(filename, |(stem, _)| stem)
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub id: u32,
+    pub job: JobInfo,
+}
+
+/// Runs download jobs through their states, broadcasting every change as an [`Event`].
+pub struct DownloadService<B = YtdlpBackend> {
+    backend: Arc<B>,
+    jobs: Jobs,
+    archive: Archive,
+    events: broadcast::Sender<Event>,
+}
+
+impl<B> Clone for DownloadService<B> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            jobs: self.jobs.clone(),
+            archive: self.archive.clone(),
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl<B: Backend + Default> Default for DownloadService<B> {
+    fn default() -> Self {
+        Self::new(B::default())
+    }
+}
+
+impl<B: Backend> DownloadService<B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            jobs: Jobs::default(),
+            archive: Archive::default(),
+            events: broadcast::channel(64).0,
+        }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn jobs(&self) -> &Jobs {
+        &self.jobs
+    }
+
+    pub fn archive(&self) -> &Archive {
+        &self.archive
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Registers a job without starting it.
+    pub fn queue(&self, url: String) -> u32 {
+        let id = self.jobs.insert(url);
+        if let Some(job) = self.jobs.get(id) {
+            let _ = self.events.send(Event { id, job });
+        }
+        id
+    }
+
+    /// Runs a queued job in the background.
+    pub fn start(&self, id: u32, url: String, options: DownloadOptions) {
+        // It may have been cancelled while waiting
+        if self.jobs.get(id).is_none_or(|job| job.state.is_finished()) {
+            return;
+        }
+        let service = self.clone();
+        let handle = tokio::spawn(async move {
+            let _ = service.run(id, &url, &options).await;
+        });
+        self.jobs.set_handle(id, handle.abort_handle());
+    }
+
+    pub fn enqueue(&self, url: String, options: DownloadOptions) -> u32 {
+        let id = self.queue(url.clone());
+        self.start(id, url, options);
+        id
+    }
+
+    /// Cancels a waiting or running job, returning whether it was either.
+    pub fn cancel(&self, id: u32) -> bool {
+        if let Some(handle) = self.jobs.take_handle(id) {
+            handle.abort();
+        } else if self
+            .jobs
+            .get(id)
+            .is_none_or(|job| job.state != JobState::Queued)
+        {
+            return false;
+        }
+        self.advance(id, JobState::Cancelled, None);
+        true
+    }
+
+    /// Runs a queued job to completion, returning its title.
+    pub async fn run(
+        &self,
+        id: u32,
+        url: &str,
+        options: &DownloadOptions,
+    ) -> Result<String, Error> {
+        let res = self.steps(id, url, options).await;
+        match &res {
+            Ok((_, note)) => self.advance(id, JobState::Done, note.clone()),
+            Err(err @ Error::Archived(_)) => {
+                self.advance(id, JobState::Skipped, Some(err.to_string()));
+            }
+            Err(err) => self.advance(id, JobState::Failed, Some(err.to_string())),
+        }
+        res.map(|(title, _)| title)
+    }
+
+    /// Runs the steps of a job, returning its title and what went wrong without
+    /// stopping it, if anything.
+    async fn steps(
+        &self,
+        id: u32,
+        url: &str,
+        options: &DownloadOptions,
+    ) -> Result<(String, Option<String>), Error> {
+        self.advance(id, JobState::FetchingMetadata, None);
+        let (title, selection) = self.backend.select(url, options).await?;
+        self.jobs.set_title(id, title.clone());
+        let key = self.backend.archive_key(&selection);
+        let archived = match &key {
+            Some(key) if !options.ignore_archive => self.archive.contains(key).await,
+            _ => false,
+        };
+        if archived {
+            return Err(Error::Archived(title));
+        }
+
+        self.advance(id, JobState::Downloading, None);
+        let downloaded = self
+            .backend
+            .download(url, &title, selection, options)
+            .await?;
+
+        self.advance(id, JobState::PostProcessing, None);
+        let mut notes: Vec<String> = self
+            .backend
+            .post_process(&title, downloaded, options)
+            .await?
+            .into_iter()
+            .collect();
+        if let Some(key) = key {
+            notes.extend(self.archive.add(&key).await.err());
+        }
+        let note = (!notes.is_empty()).then(|| notes.join("\n"));
+        Ok((title, note))
+    }
+
+    fn advance(&self, id: u32, state: JobState, error: Option<String>) {
+        if let Some(job) = self.jobs.advance(id, state, error) {
+            let _ = self.events.send(Event { id, job });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::Notify;
+
+    use super::*;
+
+    const URL: &str = "https://example.com/watch/1";
+
+    /// Backend that finishes every step at once, unless told to fail at one.
+    #[derive(Default)]
+    struct Fake {
+        /// Step to fail at.
+        fail: Option<JobState>,
+        archive_key: Option<String>,
+        /// Holds downloads until notified.
+        gate: Option<Arc<Notify>>,
+        /// What went wrong without failing the download.
+        note: Option<String>,
+    }
+
+    impl Fake {
+        fn step(&self, state: JobState, title: &str) -> Result<(), Error> {
+            if self.fail == Some(state) {
+                Err(Error::download(title, "failed on purpose"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl Backend for Fake {
+        type Selection = ();
+        type Downloaded = ();
+
+        async fn select(&self, url: &str, _: &DownloadOptions) -> Result<(String, ()), Error> {
+            let title = format!("Title of {url}");
+            self.step(JobState::FetchingMetadata, &title)?;
+            Ok((title, ()))
+        }
+
+        fn archive_key(&self, (): &()) -> Option<String> {
+            self.archive_key.clone()
+        }
+
+        async fn download(
+            &self,
+            _: &str,
+            title: &str,
+            (): (),
+            _: &DownloadOptions,
+        ) -> Result<(), Error> {
+            if let Some(gate) = &self.gate {
+                gate.notified().await;
+            }
+            self.step(JobState::Downloading, title)
+        }
+
+        async fn post_process(
+            &self,
+            title: &str,
+            (): (),
+            _: &DownloadOptions,
+        ) -> Result<Option<String>, Error> {
+            self.step(JobState::PostProcessing, title)?;
+            Ok(self.note.clone())
+        }
+    }
+
+    fn service(backend: Fake, archive: Option<PathBuf>) -> DownloadService<Fake> {
+        DownloadService {
+            backend: Arc::new(backend),
+            jobs: Jobs::default(),
+            archive: Archive::new(archive),
+            events: broadcast::channel(64).0,
+        }
+    }
+
+    /// States of the events sent so far, checking they are all for `id`.
+    fn states(events: &mut broadcast::Receiver<Event>, id: u32) -> Vec<JobState> {
+        std::iter::from_fn(|| events.try_recv().ok())
+            .inspect(|event| assert_eq!(event.id, id))
+            .map(|event| event.job.state)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn jobs_go_through_every_state() {
+        let service = service(Fake::default(), None);
+        let mut events = service.subscribe();
+        let id = service.queue(URL.to_owned());
+        let title = service.run(id, URL, &DownloadOptions::default()).await;
+
+        assert_eq!(title.unwrap(), format!("Title of {URL}"));
+        assert_eq!(
+            states(&mut events, id),
+            [
+                JobState::Queued,
+                JobState::FetchingMetadata,
+                JobState::Downloading,
+                JobState::PostProcessing,
+                JobState::Done,
+            ]
+        );
+        let job = service.jobs().get(id).unwrap();
+        assert_eq!(job.title, format!("Title of {URL}"));
+        assert_eq!(job.error, None);
+    }
+
+    #[tokio::test]
+    async fn failures_end_the_job() {
+        let steps = [
+            JobState::Queued,
+            JobState::FetchingMetadata,
+            JobState::Downloading,
+            JobState::PostProcessing,
+        ];
+        for (i, step) in steps.into_iter().enumerate().skip(1) {
+            let service = service(
+                Fake {
+                    fail: Some(step),
+                    ..Fake::default()
+                },
+                None,
+            );
+            let mut events = service.subscribe();
+            let id = service.queue(URL.to_owned());
+            let res = service.run(id, URL, &DownloadOptions::default()).await;
+
+            assert!(matches!(res, Err(Error::Download { .. })), "{step}");
+            let mut expected = steps[..=i].to_vec();
+            expected.push(JobState::Failed);
+            assert_eq!(states(&mut events, id), expected);
+            let error = service.jobs().get(id).unwrap().error.unwrap();
+            assert!(error.ends_with(": failed on purpose"), "{error}");
+        }
+    }
+
+    #[tokio::test]
+    async fn notes_are_kept_on_finished_jobs() {
+        let service = service(
+            Fake {
+                note: Some("SponsorBlock is down".to_owned()),
+                ..Fake::default()
+            },
+            None,
+        );
+        let id = service.queue(URL.to_owned());
+        service
+            .run(id, URL, &DownloadOptions::default())
+            .await
+            .unwrap();
+
+        let job = service.jobs().get(id).unwrap();
+        assert_eq!(job.state, JobState::Done);
+        assert_eq!(job.error.as_deref(), Some("SponsorBlock is down"));
+    }
+
+    #[tokio::test]
+    async fn archived_items_are_skipped() {
+        let archive = tempfile::NamedTempFile::new().unwrap();
+        let service = service(
+            Fake {
+                archive_key: Some("fake 1".to_owned()),
+                ..Fake::default()
+            },
+            Some(archive.path().to_path_buf()),
+        );
+        let options = DownloadOptions::default();
+
+        let id = service.queue(URL.to_owned());
+        service.run(id, URL, &options).await.unwrap();
+        assert!(service.archive().contains("fake 1").await);
+
+        let mut events = service.subscribe();
+        let id = service.queue(URL.to_owned());
+        let res = service.run(id, URL, &options).await;
+        assert!(matches!(res, Err(Error::Archived(_))));
+        assert_eq!(
+            states(&mut events, id),
+            [
+                JobState::Queued,
+                JobState::FetchingMetadata,
+                JobState::Skipped
+            ]
+        );
+
+        let options = DownloadOptions {
+            ignore_archive: true,
+            ..options
+        };
+        let id = service.queue(URL.to_owned());
+        service.run(id, URL, &options).await.unwrap();
+        assert_eq!(service.jobs().get(id).unwrap().state, JobState::Done);
+    }
+
+    #[tokio::test]
+    async fn queued_jobs_can_be_cancelled() {
+        let service = service(Fake::default(), None);
+        let id = service.queue(URL.to_owned());
+        let mut events = service.subscribe();
+
+        assert!(service.cancel(id));
+        assert_eq!(states(&mut events, id), [JobState::Cancelled]);
+
+        // Neither started nor cancelled again once cancelled
+        service.start(id, URL.to_owned(), DownloadOptions::default());
+        assert!(!service.cancel(id));
+        tokio::task::yield_now().await;
+        assert_eq!(service.jobs().get(id).unwrap().state, JobState::Cancelled);
+        assert!(states(&mut events, id).is_empty());
+    }
+
+    #[tokio::test]
+    async fn running_jobs_can_be_cancelled() {
+        let gate = Arc::new(Notify::new());
+        let service = service(
+            Fake {
+                gate: Some(gate.clone()),
+                ..Fake::default()
+            },
+            None,
+        );
+        let mut events = service.subscribe();
+        let id = service.enqueue(URL.to_owned(), DownloadOptions::default());
+
+        let downloading =
+            async { while events.recv().await.unwrap().job.state != JobState::Downloading {} };
+        tokio::time::timeout(Duration::from_secs(5), downloading)
+            .await
+            .unwrap();
+        assert!(service.cancel(id));
+        assert_eq!(states(&mut events, id), [JobState::Cancelled]);
+
+        // The aborted task doesn't carry on once the download would have finished
+        gate.notify_one();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(service.jobs().get(id).unwrap().state, JobState::Cancelled);
+        assert!(states(&mut events, id).is_empty());
+    }
+
+    #[test]
+    fn titles_drop_only_the_extension() {
+        assert_eq!(stem("Talk v1.2 [abc].webm"), "Talk v1.2 [abc]");
+        assert_eq!(stem("No extension"), "No extension");
+    }
+}
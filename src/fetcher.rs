@@ -1,4 +1,6 @@
 use std::{
+    fs::Permissions,
+    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
@@ -6,121 +8,836 @@ use std::{
 
 use ::yt_dlp::Youtube;
 use cosmic::Application;
-use tempfile::Builder;
+use reqwest::header::HeaderMap;
+use tempfile::{Builder, NamedTempFile, TempDir};
 use tokio::task::JoinSet;
+use url::Url;
 use yt_dlp::{
     executor::Executor,
     fetcher::{
-        Fetcher,
         deps::{Libraries, LibraryInstaller},
         download_manager::DownloadManager,
     },
+    model::Video,
     utils::find_executable,
 };
 
 use crate::applet::Ytdlp;
+use crate::archive::Archive;
+use crate::auth::Login;
+use crate::clip::{Chapter, Clip};
+use crate::config::{BinarySource, Config};
+use crate::folders;
+use crate::integrity;
+use crate::network::{self, Network, Throttle};
+use crate::rules::Metadata;
+use crate::subscriptions::Entry;
 
-pub async fn binaries() -> PathBuf {
-    // Install and update deps before running applet
-    let deps_dir = xdg::BaseDirectories::with_prefix(Ytdlp::APP_ID)
-        .expect("Failed to get xdg base dirs")
-        .get_data_home();
-    let yt_dlp_exists = deps_dir.join("yt-dlp").exists();
+/// Oldest yt-dlp release known to work with the applet.
+const MIN_YTDLP_VERSION: &str = "2024.01.01";
+
+/// Locations of the yt-dlp and ffmpeg executables.
+#[derive(Debug, Clone, Default)]
+pub struct Binaries {
+    pub yt_dlp: PathBuf,
+    pub ffmpeg: PathBuf,
+    /// Whether these are the applet's own copies, which it may update.
+    pub bundled: bool,
+    /// Executables imported without the sums to check them, by name.
+    pub unverified: Vec<&'static str>,
+    /// Where executables that changed since they were installed were moved.
+    pub quarantined: Vec<PathBuf>,
+}
+
+impl Binaries {
+    pub fn in_dir(dir: &Path) -> Self {
+        Self {
+            yt_dlp: dir.join(find_executable("yt-dlp")),
+            ffmpeg: dir.join(find_executable("ffmpeg")),
+            bundled: true,
+            unverified: vec![],
+            quarantined: vec![],
+        }
+    }
+
+    /// Looks up both executables on `PATH`.
+    pub fn on_path() -> Result<Self, String> {
+        let find = |name: &str| {
+            let path = std::env::var_os("PATH")?;
+            std::env::split_paths(&path)
+                .map(|dir| dir.join(find_executable(name)))
+                .find(|file| file.is_file())
+        };
+        Ok(Self {
+            yt_dlp: find("yt-dlp").ok_or("yt-dlp was not found on PATH")?,
+            ffmpeg: find("ffmpeg").ok_or("ffmpeg was not found on PATH")?,
+            bundled: false,
+            unverified: vec![],
+            quarantined: vec![],
+        })
+    }
+
+    pub fn libraries(&self) -> Libraries {
+        Libraries::new(self.yt_dlp.clone(), self.ffmpeg.clone())
+    }
+}
+
+/// Dependency setup steps, reported while [`binaries`] runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupStep {
+    Checking,
+    InstallingYtdlp,
+    InstallingFfmpeg,
+    UpdatingYtdlp,
+    Importing,
+}
+
+pub async fn binaries(
+    config: &Config,
+    progress: impl Fn(SetupStep) + Send,
+) -> Result<Binaries, String> {
+    progress(SetupStep::Checking);
+    let binaries = match config.binary_source {
+        BinarySource::Bundled => return bundled(config, progress).await,
+        BinarySource::System => Binaries::on_path()?,
+        BinarySource::Custom => Binaries {
+            yt_dlp: folders::expand(&config.custom_yt_dlp),
+            ffmpeg: folders::expand(&config.custom_ffmpeg),
+            bundled: false,
+            unverified: vec![],
+            quarantined: vec![],
+        },
+    };
+    validate(&binaries).await?;
+    Ok(binaries)
+}
+
+fn deps_dir() -> Result<PathBuf, String> {
+    Ok(xdg::BaseDirectories::with_prefix(Ytdlp::APP_ID)
+        .map_err(|err| format!("failed to get xdg base dirs: {err}"))?
+        .get_data_home())
+}
+
+async fn bundled(config: &Config, progress: impl Fn(SetupStep) + Send) -> Result<Binaries, String> {
+    // Install and update deps before downloading anything
+    let deps_dir = deps_dir()?;
     let installer = LibraryInstaller::new(deps_dir.clone());
 
     // Check if binaries already exist
-    let youtube_path = deps_dir.join(find_executable("yt-dlp"));
-    let ffmpeg_path = deps_dir.join(find_executable("ffmpeg"));
+    let mut binaries = Binaries::in_dir(&deps_dir);
+    // Anything changed since it was installed gets moved aside and installed again
+    for file in integrity::changed(&binaries).await {
+        binaries
+            .quarantined
+            .push(integrity::quarantine(&file).await?);
+    }
+    let yt_dlp_exists = binaries.yt_dlp.exists();
 
-    let youtube = if youtube_path.exists() {
-        youtube_path
-    } else {
-        installer
+    // Provision from local files instead of the network when configured
+    let missing = !yt_dlp_exists || !binaries.ffmpeg.exists();
+    if missing && !config.import_path.is_empty() {
+        progress(SetupStep::Importing);
+        let quarantined = binaries.quarantined;
+        return import(&folders::expand(&config.import_path))
+            .await
+            .map(|imported| Binaries {
+                quarantined,
+                ..imported
+            });
+    }
+
+    if !yt_dlp_exists {
+        progress(SetupStep::InstallingYtdlp);
+        binaries.yt_dlp = installer
             .install_youtube(None)
             .await
-            .expect("Failed to download yt-dlp")
+            .map_err(|err| format!("failed to download yt-dlp: {err}"))?;
+    }
+    if !binaries.ffmpeg.exists() {
+        progress(SetupStep::InstallingFfmpeg);
+        install_ffmpeg(&binaries.ffmpeg, &config.network).await?;
+    }
+    if !yt_dlp_exists {
+        progress(SetupStep::UpdatingYtdlp);
+        with_output_dir(&binaries, PathBuf::new(), &config.network)
+            .update_downloader()
+            .await
+            .map_err(|err| format!("failed to update yt-dlp: {err}"))?;
+        let version = ytdlp_version(&binaries)
+            .await
+            .ok_or("the installed yt-dlp failed to run")?;
+        let verified =
+            integrity::verify_published(&binaries.yt_dlp, &version, &config.network).await;
+        if let Err(err) = verified {
+            let target = integrity::quarantine(&binaries.yt_dlp).await?;
+            return Err(format!("{err}, moved it to {}", target.display()));
+        }
+    }
+    integrity::record(&binaries).await?;
+    Ok(binaries)
+}
+
+/// Name of the ffmpeg build archive for this architecture.
+fn ffmpeg_archive() -> Result<String, String> {
+    let platform = match std::env::consts::ARCH {
+        "x86_64" => "linux64",
+        "aarch64" => "linuxarm64",
+        arch => return Err(format!("no ffmpeg builds are published for {arch}")),
     };
+    Ok(format!("ffmpeg-master-latest-{platform}-gpl.tar.xz"))
+}
+
+/// Downloads the ffmpeg build for this architecture and installs it as `target`,
+/// once the archive matches the sums published with it.
+async fn install_ffmpeg(target: &Path, network: &Network) -> Result<(), String> {
+    let name = ffmpeg_archive()?;
+    let dir = tempfile::tempdir().map_err(|err| format!("failed to create temp dir: {err}"))?;
+    let archive = dir.path().join(&name);
+    let url = format!("{}/{name}", integrity::FFMPEG_RELEASE);
+    network
+        .download(&url, &archive, HeaderMap::new())
+        .await
+        .map_err(|err| format!("failed to download ffmpeg: {err}"))?;
+    integrity::verify_ffmpeg(&archive, &name, network).await?;
+    extract_ffmpeg(&archive, target).await
+}
+
+/// Extracts the ffmpeg executable of a checked build archive to `target`.
+async fn extract_ffmpeg(archive: &Path, target: &Path) -> Result<(), String> {
+    let dir = tempfile::tempdir().map_err(|err| format!("failed to create temp dir: {err}"))?;
+    untar(archive, dir.path()).await?;
+    let ffmpeg = find_file(dir.path(), &["ffmpeg"])
+        .ok_or_else(|| format!("ffmpeg was not found in {}", archive.display()))?;
+    // Copied next to the target first, so it's never left half written
+    let parent = target.parent().unwrap_or(Path::new("."));
+    let staged = NamedTempFile::new_in(parent)
+        .map_err(|err| format!("failed to create temp file: {err}"))?;
+    tokio::fs::copy(&ffmpeg, staged.path())
+        .await
+        .map_err(|err| format!("failed to copy ffmpeg: {err}"))?;
+    tokio::fs::set_permissions(staged.path(), Permissions::from_mode(0o755))
+        .await
+        .map_err(|err| format!("failed to make ffmpeg executable: {err}"))?;
+    staged
+        .persist(target)
+        .map_err(|err| format!("failed to install {}: {err}", target.display()))?;
+    Ok(())
+}
 
-    let ffmpeg = if ffmpeg_path.exists() {
-        ffmpeg_path
+async fn untar(archive: &Path, dir: &Path) -> Result<(), String> {
+    let status = tokio::process::Command::new("tar")
+        .arg("-xf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dir)
+        .status()
+        .await
+        .map_err(|err| format!("failed to run tar: {err}"))?;
+    if status.success() {
+        Ok(())
     } else {
-        installer
-            .install_ffmpeg(None)
-            .await
-            .expect("Failed to download ffmpeg")
+        Err(format!("failed to extract {}", archive.display()))
+    }
+}
+
+/// Copies yt-dlp and ffmpeg from a local directory or tar archive into the
+/// dependency directory, for machines without network access. yt-dlp is checked
+/// against the `SHA2-256SUMS` file of its release and ffmpeg's build archive against
+/// the `checksums.sha256` file of its release. Without those files they can't be
+/// checked offline, and are imported as [`Binaries::unverified`].
+///
+/// Both are checked and tried out in a staging directory first, so a failed import
+/// leaves the installed executables as they were.
+pub async fn import(source: &Path) -> Result<Binaries, String> {
+    let extracted;
+    let dir = if source.is_dir() {
+        source.to_path_buf()
+    } else {
+        extracted =
+            tempfile::tempdir().map_err(|err| format!("failed to create temp dir: {err}"))?;
+        untar(source, extracted.path()).await?;
+        extracted.path().to_path_buf()
     };
-    if !yt_dlp_exists {
-        Youtube {
-            libraries: Libraries::new(youtube, ffmpeg),
-            output_dir: "".into(),
-            args: vec![],
-            timeout: Duration::from_secs(30),
-            cache: None,
-            download_cache: None,
-            download_manager: Arc::new(DownloadManager::new()),
+
+    let deps_dir = deps_dir()?;
+    tokio::fs::create_dir_all(&deps_dir)
+        .await
+        .map_err(|err| format!("failed to create {}: {err}", deps_dir.display()))?;
+    // Staged on the same file system, so moving into place can't fail halfway
+    let staging = Builder::new()
+        .prefix(".import")
+        .tempdir_in(&deps_dir)
+        .map_err(|err| format!("failed to create temp dir: {err}"))?;
+    let mut staged = Binaries::in_dir(staging.path());
+    if !stage_yt_dlp(&dir, source, &staged.yt_dlp).await? {
+        staged.unverified.push("yt-dlp");
+    }
+    if !stage_ffmpeg(&dir, source, &staged.ffmpeg).await? {
+        staged.unverified.push("ffmpeg");
+    }
+    validate(&staged).await?;
+
+    let binaries = Binaries {
+        unverified: staged.unverified.clone(),
+        ..Binaries::in_dir(&deps_dir)
+    };
+    for (from, to) in [
+        (&staged.yt_dlp, &binaries.yt_dlp),
+        (&staged.ffmpeg, &binaries.ffmpeg),
+    ] {
+        tokio::fs::rename(from, to)
+            .await
+            .map_err(|err| format!("failed to install {}: {err}", to.display()))?;
+    }
+    integrity::record(&binaries).await?;
+    Ok(binaries)
+}
+
+/// Copies the yt-dlp found in `dir` to `target`, checking it against the sums of its
+/// release if they come along. Returns whether it was checked.
+async fn stage_yt_dlp(dir: &Path, source: &Path, target: &Path) -> Result<bool, String> {
+    let yt_dlp = find_file(dir, &["yt-dlp", "yt-dlp_linux", "yt-dlp_linux_aarch64"])
+        .ok_or_else(|| format!("yt-dlp was not found in {}", source.display()))?;
+    let sums = find_file(dir, &["SHA2-256SUMS"]);
+    if let Some(sums) = &sums {
+        integrity::verify_sums(&yt_dlp, sums).await?;
+    }
+    tokio::fs::copy(&yt_dlp, target)
+        .await
+        .map_err(|err| format!("failed to copy {}: {err}", yt_dlp.display()))?;
+    tokio::fs::set_permissions(target, Permissions::from_mode(0o755))
+        .await
+        .map_err(|err| format!("failed to make yt-dlp executable: {err}"))?;
+    Ok(sums.is_some())
+}
+
+/// Extracts ffmpeg to `target` from the build archive found in `dir`, checking it
+/// against the sums of its release, or copies an ffmpeg executable found there
+/// instead. Returns whether it was checked.
+async fn stage_ffmpeg(dir: &Path, source: &Path, target: &Path) -> Result<bool, String> {
+    let name = ffmpeg_archive()?;
+    let archive = find_file(dir, &[&name]);
+    let sums = find_file(dir, &[integrity::FFMPEG_SUMS]);
+    if let (Some(archive), Some(sums)) = (&archive, &sums) {
+        let sums = tokio::fs::read_to_string(sums)
+            .await
+            .map_err(|err| format!("failed to read {}: {err}", sums.display()))?;
+        integrity::verify_ffmpeg_sums(archive, &name, &sums).await?;
+        extract_ffmpeg(archive, target).await?;
+        return Ok(true);
+    }
+    let ffmpeg = find_file(dir, &["ffmpeg"])
+        .ok_or_else(|| format!("ffmpeg was not found in {}", source.display()))?;
+    tokio::fs::copy(&ffmpeg, target)
+        .await
+        .map_err(|err| format!("failed to copy {}: {err}", ffmpeg.display()))?;
+    tokio::fs::set_permissions(target, Permissions::from_mode(0o755))
+        .await
+        .map_err(|err| format!("failed to make ffmpeg executable: {err}"))?;
+    Ok(false)
+}
+
+/// Finds the first file named after one of `names`, searching `dir` recursively.
+fn find_file(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    let mut dirs = vec![];
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            dirs.push(path);
+        } else if names.iter().any(|name| entry.file_name() == *name) {
+            return Some(path);
         }
-        .update_downloader()
+    }
+    dirs.iter().find_map(|dir| find_file(dir, names))
+}
+
+/// Checks that externally managed binaries run and support what the applet needs.
+async fn validate(binaries: &Binaries) -> Result<(), String> {
+    let version = ytdlp_version(binaries)
         .await
-        .unwrap();
+        .ok_or_else(|| format!("{} doesn't run", binaries.yt_dlp.display()))?;
+    // Versions are dates, so they compare as strings
+    if version.as_str() < MIN_YTDLP_VERSION {
+        return Err(format!(
+            "yt-dlp {version} is too old, {MIN_YTDLP_VERSION} or newer is required"
+        ));
     }
-    deps_dir
+
+    let ffmpeg = binaries.ffmpeg.display();
+    let lists = |list: &'static str| Executor {
+        executable_path: binaries.ffmpeg.clone(),
+        timeout: Duration::from_secs(30),
+        args: yt_dlp::utils::to_owned(vec!["-hide_banner", list]),
+    };
+    let protocols = lists("-protocols")
+        .execute()
+        .await
+        .map_err(|_| format!("{ffmpeg} doesn't run"))?
+        .stdout;
+    let muxers = lists("-muxers")
+        .execute()
+        .await
+        .map_err(|_| format!("{ffmpeg} doesn't run"))?
+        .stdout;
+    let has = |output: &str, name: &str| {
+        output
+            .lines()
+            .any(|line| line.split_whitespace().any(|word| word == name))
+    };
+    if !has(&protocols, "concat") {
+        return Err(format!("{ffmpeg} lacks the concat protocol"));
+    }
+    for muxer in ["mp4", "ipod"] {
+        if !has(&muxers, muxer) {
+            return Err(format!("{ffmpeg} lacks the {muxer} muxer"));
+        }
+    }
+    Ok(())
 }
 
-pub fn with_output_dir(lib_dir: &Path, output_dir: PathBuf) -> Youtube {
-    let youtube = lib_dir.join("yt-dlp");
-    let ffmpeg = lib_dir.join("ffmpeg");
-    let libraries = Libraries::new(youtube, ffmpeg);
+pub fn with_output_dir(binaries: &Binaries, output_dir: PathBuf, network: &Network) -> Youtube {
     Youtube {
-        libraries,
+        libraries: binaries.libraries(),
         output_dir,
-        args: vec![],
-        timeout: Duration::from_secs(30),
+        args: network.ytdlp_args(),
+        timeout: network.timeout(),
         cache: None,
         download_cache: None,
         download_manager: Arc::new(DownloadManager::new()),
     }
-    // libs, output_dir)
 }
 
+/// Version reported by `yt-dlp --version`.
+pub async fn ytdlp_version(binaries: &Binaries) -> Option<String> {
+    let executor = Executor {
+        executable_path: binaries.yt_dlp.clone(),
+        timeout: Duration::from_secs(30),
+        args: yt_dlp::utils::to_owned(vec!["--version"]),
+    };
+    let output = executor.execute().await.ok()?;
+    let version = output.stdout.trim();
+    (!version.is_empty()).then(|| version.to_owned())
+}
+
+/// Latest yt-dlp release, taken from where GitHub's latest release page redirects to.
+pub async fn latest_ytdlp_version(network: Network) -> Option<String> {
+    let response = network
+        .client()
+        .ok()?
+        .get(format!("{}/latest", integrity::releases_url()))
+        .send()
+        .await
+        .ok()?;
+    let tag = response.url().path_segments()?.next_back()?;
+    (tag != "latest").then(|| tag.to_owned())
+}
+
+/// Updates yt-dlp, restoring the previous binary if the new one doesn't run.
+/// Returns the new version.
+pub async fn update_ytdlp(binaries: &Binaries, network: &Network) -> Result<String, String> {
+    let backup = binaries.yt_dlp.with_extension("bak");
+    tokio::fs::copy(&binaries.yt_dlp, &backup)
+        .await
+        .map_err(|err| format!("failed to back up yt-dlp: {err}"))?;
+
+    let updated = with_output_dir(binaries, PathBuf::new(), network)
+        .update_downloader()
+        .await;
+    // Smoke test and verify the new binary before trusting it
+    let checked = match updated {
+        Ok(_) => match ytdlp_version(binaries).await {
+            Some(version) => integrity::verify_published(&binaries.yt_dlp, &version, network)
+                .await
+                .map(|()| version),
+            None => Err(String::from("the updated yt-dlp failed to run")),
+        },
+        Err(err) => Err(format!("failed to update yt-dlp: {err}")),
+    };
+    match checked {
+        Ok(version) => {
+            let _ = tokio::fs::remove_file(&backup).await;
+            integrity::record(binaries).await?;
+            Ok(version)
+        }
+        Err(err) => {
+            tokio::fs::rename(&backup, &binaries.yt_dlp)
+                .await
+                .map_err(|err| format!("failed to restore yt-dlp: {err}"))?;
+            Err(format!("{err}, restored the previous version"))
+        }
+    }
+}
+
+/// What yt-dlp makes of a URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Support {
+    /// Handled by the named extractor.
+    Extractor(String),
+    /// Only matched by the generic extractor, which looks for media in the page.
+    Generic,
+    Unsupported,
+    /// The check failed or timed out, leaving yt-dlp to report on the download.
+    Unknown,
+}
+
+/// Asks yt-dlp which extractor handles `url`, without downloading anything.
+pub async fn extractor(binaries: &Binaries, network: &Network, url: &str) -> Support {
+    let mut command = tokio::process::Command::new(&binaries.yt_dlp);
+    command
+        .args(network.ytdlp_args())
+        .args([
+            "--flat-playlist",
+            "--no-warnings",
+            "--playlist-items",
+            "1",
+            "--print",
+            "extractor_key",
+            url,
+        ])
+        .kill_on_drop(true);
+    match tokio::time::timeout(network.timeout(), command.output()).await {
+        Ok(Ok(output)) => support(
+            output.status.success(),
+            &String::from_utf8_lossy(&output.stdout),
+            &String::from_utf8_lossy(&output.stderr),
+        ),
+        _ => Support::Unknown,
+    }
+}
+
+/// Reads the extractor key yt-dlp printed, or the error it reported instead.
+fn support(success: bool, stdout: &str, stderr: &str) -> Support {
+    if !success {
+        // yt-dlp reports errors on lines of their own, and the generic extractor
+        // raises `UnsupportedError` when it finds no media in the page
+        let unsupported = stderr
+            .lines()
+            .filter_map(|line| line.strip_prefix("ERROR: "))
+            .any(|error| error.starts_with("Unsupported URL: "));
+        return if unsupported {
+            Support::Unsupported
+        } else {
+            Support::Unknown
+        };
+    }
+    match stdout.lines().next().map(str::trim) {
+        Some("Generic") => Support::Generic,
+        Some(extractor) if !extractor.is_empty() => Support::Extractor(extractor.to_owned()),
+        _ => Support::Unknown,
+    }
+}
+
+/// Lists the first `limit` items of a channel or playlist, without fetching each of them.
+pub async fn playlist_entries(
+    binaries: &Binaries,
+    mut args: Vec<String>,
+    url: &str,
+    limit: usize,
+) -> Result<Vec<Entry>, String> {
+    let items = format!("1:{limit}");
+    args.extend(yt_dlp::utils::to_owned(vec![
+        "--flat-playlist",
+        "--no-warnings",
+        "--playlist-items",
+        &items,
+        "--print",
+        "%(ie_key,extractor_key)s %(id)s %(webpage_url,url)s",
+        url,
+    ]));
+    let executor = Executor {
+        executable_path: binaries.yt_dlp.clone(),
+        timeout: Duration::from_secs(120),
+        args,
+    };
+    let output = executor
+        .execute()
+        .await
+        .map_err(|err| format!("failed to list {url}: {err}"))?;
+    Ok(output
+        .stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().splitn(3, ' ');
+            let (extractor, id, url) = (fields.next()?, fields.next()?, fields.next()?);
+            (id != "NA" && url != "NA").then(|| Entry {
+                key: Archive::key(extractor, id),
+                url: url.to_owned(),
+            })
+        })
+        .collect())
+}
+
+/// Chapters of the item at `url`, empty if it has none.
+pub async fn chapters(
+    binaries: &Binaries,
+    mut args: Vec<String>,
+    url: &str,
+) -> Result<Vec<Chapter>, String> {
+    args.extend(yt_dlp::utils::to_owned(vec![
+        "--no-playlist",
+        "--no-warnings",
+        "--print",
+        "%(chapters)j",
+        url,
+    ]));
+    let executor = Executor {
+        executable_path: binaries.yt_dlp.clone(),
+        timeout: Duration::from_secs(30),
+        args,
+    };
+    let output = executor
+        .execute()
+        .await
+        .map_err(|err| format!("failed to get chapters of {url}: {err}"))?;
+    let chapters: Option<Vec<Chapter>> = serde_json::from_str(output.stdout.trim())
+        .map_err(|err| format!("failed to read chapters of {url}: {err}"))?;
+    Ok(chapters.unwrap_or_default())
+}
+
+/// What yt-dlp reports about a single item, fetched once per download and read
+/// as needed.
+pub struct Info {
+    pub video: Video,
+    json: serde_json::Value,
+}
+
+/// Fetches the info JSON of the item at `url`.
+pub async fn info(
+    binaries: &Binaries,
+    network: &Network,
+    login: &Login,
+    url: &str,
+) -> Result<Info, String> {
+    let mut args = network.ytdlp_args();
+    args.extend(login.args.iter().cloned());
+    args.extend(yt_dlp::utils::to_owned(vec![
+        "--dump-single-json",
+        "--no-playlist",
+        "--no-warnings",
+        url,
+    ]));
+    let executor = Executor {
+        executable_path: binaries.yt_dlp.clone(),
+        timeout: network.timeout(),
+        args,
+    };
+    let output = executor
+        .execute()
+        .await
+        .map_err(|err| format!("failed to get metadata of {url}: {err}"))?;
+    let json: serde_json::Value = serde_json::from_str(output.stdout.trim())
+        .map_err(|err| format!("failed to read metadata of {url}: {err}"))?;
+    let video = serde_json::from_value(json.clone())
+        .map_err(|err| format!("failed to read metadata of {url}: {err}"))?;
+    Ok(Info { video, json })
+}
+
+impl Info {
+    /// First of `fields` that is a non-empty string, like yt-dlp's `%(a,b)s`.
+    fn field(&self, fields: &[&str]) -> Option<String> {
+        fields
+            .iter()
+            .filter_map(|field| self.json.get(field)?.as_str())
+            .map(str::trim)
+            .find(|value| !value.is_empty())
+            .map(str::to_owned)
+    }
+
+    /// Download archive key of the item.
+    pub fn archive_key(&self) -> Option<String> {
+        let extractor = self.field(&["extractor_key", "ie_key"])?;
+        let id = self.field(&["id"])?;
+        Some(Archive::key(&extractor, &id))
+    }
+
+    /// Chapters of the item, empty if it has none.
+    pub fn chapters(&self) -> Vec<Chapter> {
+        let chapters = self.json.get("chapters").cloned().unwrap_or_default();
+        serde_json::from_value::<Option<Vec<Chapter>>>(chapters)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    /// Where the item at `url` comes from, for the folder rules. The playlist is
+    /// only known when yt-dlp reports the item as part of one.
+    pub fn metadata(&self, url: &str) -> Metadata {
+        let domain = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+            .unwrap_or_default();
+        Metadata {
+            domain,
+            uploader: self.field(&["uploader", "channel"]),
+            playlist: self.field(&["playlist_title", "playlist"]),
+        }
+    }
+
+    /// Fills a yt-dlp output template, e.g. `%(uploader)s - %(title)s`, from the
+    /// fetched info instead of extracting the item again.
+    pub async fn filename(&self, binaries: &Binaries, template: &str) -> Result<String, String> {
+        let file =
+            NamedTempFile::new().map_err(|err| format!("failed to create temp file: {err}"))?;
+        tokio::fs::write(file.path(), self.json.to_string())
+            .await
+            .map_err(|err| format!("failed to write {}: {err}", file.path().display()))?;
+        let mut args = yt_dlp::utils::to_owned(vec!["--no-warnings", "--load-info-json"]);
+        args.push(file.path().to_string_lossy().into_owned());
+        args.extend(yt_dlp::utils::to_owned(vec![
+            "--output", template, "--print", "filename",
+        ]));
+        let executor = Executor {
+            executable_path: binaries.yt_dlp.clone(),
+            timeout: Duration::from_secs(30),
+            args,
+        };
+        let output = executor
+            .execute()
+            .await
+            .map_err(|err| format!("failed to fill '{template}': {err}"))?;
+        let filename = output.stdout.lines().next().unwrap_or_default().trim();
+        if filename.is_empty() {
+            return Err(format!("'{template}' gave an empty file name"));
+        }
+        Ok(filename.to_owned())
+    }
+}
+
+/// Lets yt-dlp download and post-process a format itself, for options the
+/// native downloader doesn't support. Returns the final file.
+pub async fn download_with_ytdlp(
+    binaries: &Binaries,
+    mut args: Vec<String>,
+    format_id: &str,
+    output_dir: &Path,
+    title: &str,
+    url: &str,
+) -> Result<PathBuf, String> {
+    // The title is used as a literal part of the output template
+    let output = format!("{}.%(ext)s", title.replace('%', "%%"));
+    args.extend([
+        String::from("--no-playlist"),
+        String::from("--format"),
+        format_id.to_owned(),
+        String::from("--ffmpeg-location"),
+        binaries.ffmpeg.to_string_lossy().into_owned(),
+        String::from("--paths"),
+        output_dir.to_string_lossy().into_owned(),
+        String::from("--output"),
+        output,
+        String::from("--print"),
+        String::from("after_move:filepath"),
+        url.to_owned(),
+    ]);
+    let executor = Executor {
+        executable_path: binaries.yt_dlp.clone(),
+        timeout: Duration::default(),
+        args,
+    };
+    let output = executor
+        .execute()
+        .await
+        .map_err(|err| format!("yt-dlp failed: {err}"))?;
+    let file = output.stdout.lines().next_back().unwrap_or_default().trim();
+    if file.is_empty() {
+        return Err(String::from("yt-dlp didn't say where the file was saved"));
+    }
+    Ok(PathBuf::from(file))
+}
+
+/// HLS segments downloaded into a temporary directory, waiting to be joined.
+pub struct Segments {
+    dir: TempDir,
+    files: Vec<String>,
+    /// Range to cut out of the joined segments.
+    cut: Clip,
+}
+
+/// Downloads the segments of the HLS playlist at `url`, only those covering `clip` if
+/// it's set. Segment URIs are resolved against the playlist, so the playlist URL
+/// decides which server everything comes from.
 pub async fn manifest(
-    url: String,
-    output_dir: PathBuf,
-    ffmpeg: PathBuf,
-    title: &String,
-    video_selected: bool,
-) -> bool {
+    url: &str,
+    output_dir: &Path,
+    network: &Network,
+    login: &Login,
+    clip: &Clip,
+) -> Result<Segments, String> {
     let dir = Builder::new()
         .prefix(".yt-dlp")
-        .tempdir_in(&output_dir)
-        .expect("Failed to create temp dir");
-    let file = output_dir.join(format!("{title}.m3u8"));
-    let base_url = Path::new(&url).parent().unwrap();
-    if Fetcher::new(&url).fetch_asset(&file).await.is_err() {
-        return false;
-    }
-    let Ok(m3u8) = tokio::fs::read(&file).await else {
-        return false;
-    };
-    let (_, parsed) = m3u8_rs::parse_media_playlist(&m3u8).expect("Failed to parse");
+        .tempdir_in(output_dir)
+        .map_err(|err| format!("failed to create temp dir: {err}"))?;
+    // Kept with the segments, so only the joined file is left behind
+    let file = dir.path().join("playlist.m3u8");
+    // Segment URIs are relative to the playlist, whichever host serves it
+    let base_url = Url::parse(url).map_err(|err| format!("invalid playlist URL {url}: {err}"))?;
+    let client = network.client()?;
+    let throttle = Throttle::new(network.rate_limit());
+    let retries = network.retries();
+    network::fetch(&client, &throttle, retries, url, &login.headers(url), &file)
+        .await
+        .map_err(|err| format!("failed to download the playlist: {err}"))?;
+    let m3u8 = tokio::fs::read(&file)
+        .await
+        .map_err(|err| format!("failed to read {}: {err}", file.display()))?;
+    let (_, parsed) = m3u8_rs::parse_media_playlist(&m3u8)
+        .map_err(|err| format!("invalid playlist {url}: {err}"))?;
+    let mut segments = parsed.segments;
+    let mut cut = Clip::default();
+    if clip.is_set() {
+        let durations: Vec<f64> = segments.iter().map(|s| f64::from(s.duration)).collect();
+        let (first, last, relative) = clip
+            .segments(&durations)
+            .ok_or("the clip is outside of the playlist")?;
+        segments = segments.drain(first..=last).collect();
+        cut = relative;
+    }
     let mut files = vec![];
     let mut set = JoinSet::new();
-    for segment in parsed.segments {
-        let file_name = dir
-            .path()
-            .join(Path::new(&segment.uri).file_name().unwrap());
-        let segment_url = base_url.join(segment.uri).to_str().unwrap().to_owned();
-        files.push(file_name.to_str().unwrap().to_owned());
-        set.spawn(async { Fetcher::new(segment_url).fetch_asset(file_name).await });
-    }
-    if set.join_all().await.iter().any(Result::is_err) {
-        return false;
-    }
-    let concat = format!("concat:{}", files.join("|"));
+    for segment in segments {
+        let invalid = || format!("invalid segment {}", segment.uri);
+        let file_name = Path::new(&segment.uri).file_name().ok_or_else(invalid)?;
+        let file_name = dir.path().join(file_name);
+        let segment_url = base_url.join(&segment.uri).map_err(|_| invalid())?;
+        let segment_url = segment_url.to_string();
+        // Segments are often served by a CDN that mustn't see the site's login
+        let headers = login.headers(&segment_url);
+        files.push(file_name.to_str().ok_or_else(invalid)?.to_owned());
+        let (client, throttle) = (client.clone(), throttle.clone());
+        set.spawn(async move {
+            network::fetch(
+                &client,
+                &throttle,
+                retries,
+                &segment_url,
+                &headers,
+                &file_name,
+            )
+            .await
+        });
+    }
+    if let Some(Err(err)) = set.join_all().await.into_iter().find(Result::is_err) {
+        return Err(format!("failed to download a segment: {err}"));
+    }
+    Ok(Segments { dir, files, cut })
+}
+
+/// Joins downloaded segments into a single file with ffmpeg, returning the file.
+pub async fn concat(
+    segments: Segments,
+    output_dir: &Path,
+    ffmpeg: PathBuf,
+    title: &str,
+    video_selected: bool,
+) -> Result<PathBuf, String> {
+    let concat = format!("concat:{}", segments.files.join("|"));
     let file_name = output_dir.join(format!(
         "{title}.{}",
         if video_selected { "mp4" } else { "m4a" }
     ));
+    let cut = segments.cut.ffmpeg_args();
     let mut args = vec!["-i", &concat];
+    args.extend(cut.iter().map(String::as_str));
     args.extend_from_slice(&["-hwaccel", "auto", "-c:a", "copy", "-acodec", "copy"]);
     if video_selected {
         args.extend_from_slice(&["-c:v", "copy", "-vcodec", "copy"]);
@@ -132,5 +849,37 @@ pub async fn manifest(
         timeout: Duration::default(),
         args: yt_dlp::utils::to_owned(args),
     };
-    executor.execute().await.is_ok()
+    let res = executor.execute().await;
+    // Segments are only needed until they're joined
+    drop(segments.dir);
+    res.map(|_| file_name)
+        .map_err(|err| format!("failed to join the segments: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extractor_keys_are_read_from_the_output() {
+        assert_eq!(
+            support(true, "Youtube\n", ""),
+            Support::Extractor(String::from("Youtube"))
+        );
+        assert_eq!(support(true, "Generic\n", ""), Support::Generic);
+        assert_eq!(support(true, "", ""), Support::Unknown);
+    }
+
+    #[test]
+    fn only_unsupported_url_errors_mean_unsupported() {
+        let unsupported = "WARNING: [generic] Falling back on generic information extractor\n\
+            ERROR: Unsupported URL: https://example.com/page\n";
+        assert_eq!(support(false, "", unsupported), Support::Unsupported);
+        let unavailable = "ERROR: [youtube] abc: Video unavailable\n";
+        assert_eq!(support(false, "", unavailable), Support::Unknown);
+        // A title mentioning the error isn't one
+        let offline = "ERROR: [youtube] abc: Unsupported URL: is the title\n";
+        assert_eq!(support(false, "", offline), Support::Unknown);
+        assert_eq!(support(false, "Youtube\n", ""), Support::Unknown);
+    }
 }
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Download folders: expanding `~`, checking they can be written to and have room.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use rustix::fs::Access;
+
+use crate::fl;
+
+/// Replaces a leading `~` with the home folder, as a shell would.
+pub fn expand(path: &str) -> PathBuf {
+    let path = path.trim();
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    match path.strip_prefix('~') {
+        Some("") => home().unwrap_or_else(|| PathBuf::from(path)),
+        Some(rest) if rest.starts_with('/') => home().map_or_else(
+            || PathBuf::from(path),
+            |home| home.join(rest.trim_start_matches('/')),
+        ),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Why files can't be saved to a folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    Missing,
+    NotFolder,
+    ReadOnly,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Missing => write!(f, "{}", fl!("folder-missing")),
+            Problem::NotFolder => write!(f, "{}", fl!("folder-not-folder")),
+            Problem::ReadOnly => write!(f, "{}", fl!("folder-read-only")),
+        }
+    }
+}
+
+/// Checks that `folder` exists and new files can be created in it.
+pub fn check(folder: &Path) -> Result<(), Problem> {
+    let metadata = std::fs::metadata(folder).map_err(|_| Problem::Missing)?;
+    if !metadata.is_dir() {
+        return Err(Problem::NotFolder);
+    }
+    rustix::fs::access(folder, Access::WRITE_OK | Access::EXEC_OK).map_err(|_| Problem::ReadOnly)
+}
+
+/// Bytes available to the user on the file system holding `folder`.
+pub fn free_space(folder: &Path) -> Option<u64> {
+    let stat = rustix::fs::statvfs(folder).ok()?;
+    Some(stat.f_bavail.saturating_mul(stat.f_frsize))
+}
+
+/// Formats a size in decimal units, e.g. `1.5 GB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tildes_expand_to_home() {
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        assert_eq!(expand("~"), home);
+        assert_eq!(expand(" ~/Videos "), home.join("Videos"));
+        assert_eq!(expand("~//Videos"), home.join("Videos"));
+    }
+
+    #[test]
+    fn other_paths_stay_as_they_are() {
+        assert_eq!(expand("/srv/videos"), PathBuf::from("/srv/videos"));
+        assert_eq!(expand("videos/~"), PathBuf::from("videos/~"));
+        // Other users' homes aren't looked up
+        assert_eq!(expand("~alice/videos"), PathBuf::from("~alice/videos"));
+    }
+
+    #[test]
+    fn folders_are_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+
+        assert_eq!(check(dir.path()), Ok(()));
+        assert_eq!(check(&dir.path().join("missing")), Err(Problem::Missing));
+        assert_eq!(check(&file), Err(Problem::NotFolder));
+    }
+
+    #[test]
+    fn sizes_use_decimal_units() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(999), "999 B");
+        assert_eq!(format_size(1000), "1.0 KB");
+        assert_eq!(format_size(1_500_000_000), "1.5 GB");
+        // There's no unit past terabytes
+        assert_eq!(format_size(2_000_000_000_000_000), "2000.0 TB");
+    }
+}
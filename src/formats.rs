@@ -1,7 +1,19 @@
 use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::auth::Auth;
+use crate::clip::{self, Clip};
+use crate::folders;
+use crate::network::{self, Network};
+use crate::rules::FolderRule;
+use crate::split::Split;
+use crate::sponsorblock::SponsorBlock;
 
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum VideoQuality {
     #[default]
     Highest,
@@ -23,6 +35,18 @@ impl Display for VideoQuality {
     }
 }
 
+impl VideoQuality {
+    pub const ALL: [Self; 5] = [Self::Highest, Self::FHD, Self::HD, Self::SD, Self::Lowest];
+}
+
+impl FromStr for VideoQuality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(&Self::ALL, s)
+    }
+}
+
 impl From<VideoQuality> for yt_dlp::model::VideoQuality {
     fn from(val: VideoQuality) -> Self {
         match val {
@@ -35,7 +59,7 @@ impl From<VideoQuality> for yt_dlp::model::VideoQuality {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum VideoCodec {
     AV1,
     AVC1,
@@ -55,6 +79,18 @@ impl Display for VideoCodec {
     }
 }
 
+impl VideoCodec {
+    pub const ALL: [Self; 4] = [Self::AV1, Self::AVC1, Self::VP9, Self::Any];
+}
+
+impl FromStr for VideoCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(&Self::ALL, s)
+    }
+}
+
 impl From<VideoCodec> for yt_dlp::model::VideoCodecPreference {
     fn from(val: VideoCodec) -> Self {
         match val {
@@ -66,7 +102,7 @@ impl From<VideoCodec> for yt_dlp::model::VideoCodecPreference {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum AudioQuality {
     #[default]
     Best,
@@ -88,6 +124,18 @@ impl Display for AudioQuality {
     }
 }
 
+impl AudioQuality {
+    pub const ALL: [Self; 5] = [Self::Best, Self::High, Self::Medium, Self::Low, Self::Worst];
+}
+
+impl FromStr for AudioQuality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(&Self::ALL, s)
+    }
+}
+
 impl From<AudioQuality> for yt_dlp::model::AudioQuality {
     fn from(val: AudioQuality) -> Self {
         match val {
@@ -101,7 +149,7 @@ impl From<AudioQuality> for yt_dlp::model::AudioQuality {
 }
 
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum AudioCodec {
     Opus,
     ACC,
@@ -121,6 +169,18 @@ impl Display for AudioCodec {
     }
 }
 
+impl AudioCodec {
+    pub const ALL: [Self; 4] = [Self::Opus, Self::ACC, Self::MP3, Self::Any];
+}
+
+impl FromStr for AudioCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(&Self::ALL, s)
+    }
+}
+
 impl From<AudioCodec> for yt_dlp::model::AudioCodecPreference {
     fn from(val: AudioCodec) -> Self {
         match val {
@@ -131,3 +191,92 @@ impl From<AudioCodec> for yt_dlp::model::AudioCodecPreference {
         }
     }
 }
+
+/// Matches a value against the displayed names, ignoring case.
+fn parse<T: Display + Copy>(all: &[T], s: &str) -> Result<T, String> {
+    all.iter()
+        .find(|value| value.to_string().eq_ignore_ascii_case(s))
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<_> = all.iter().map(ToString::to_string).collect();
+            format!("invalid value '{s}', expected one of: {}", names.join(", "))
+        })
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{value}', expected true or false"))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadOptions {
+    pub video: bool,
+    pub video_quality: VideoQuality,
+    pub video_codec: VideoCodec,
+    pub audio_quality: AudioQuality,
+    pub audio_codec: AudioCodec,
+    pub output_dir: PathBuf,
+    pub network: Network,
+    pub auth: Auth,
+    /// yt-dlp output template for the file name, without the extension.
+    pub filename_template: String,
+    /// Extra arguments that make yt-dlp download the file itself.
+    pub extra_args: Vec<String>,
+    /// Download even if the item is in the download archive.
+    pub ignore_archive: bool,
+    pub clip: Clip,
+    pub split: Split,
+    pub sponsorblock: SponsorBlock,
+    /// Rules that may move the download out of `output_dir`.
+    pub folder_rules: Vec<FolderRule>,
+}
+
+impl DownloadOptions {
+    /// Overrides a single option by name, as received from other applications.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "type" => {
+                self.video = match value {
+                    "video" => true,
+                    "audio" => false,
+                    _ => return Err(format!("invalid type '{value}', expected video or audio")),
+                }
+            }
+            "video-quality" => self.video_quality = value.parse()?,
+            "video-codec" => self.video_codec = value.parse()?,
+            "audio-quality" => self.audio_quality = value.parse()?,
+            "audio-codec" => self.audio_codec = value.parse()?,
+            "folder" => self.output_dir = folders::expand(value),
+            // Empty values clear the setting
+            "proxy" => {
+                if !value.trim().is_empty() {
+                    network::parse_proxy(value)?;
+                }
+                self.network.proxy = value.to_owned();
+            }
+            "rate-limit" => {
+                if !value.trim().is_empty() && network::parse_rate_limit(value).is_none() {
+                    return Err(format!(
+                        "invalid rate limit '{value}', expected bytes per second, e.g. 500K or 2M"
+                    ));
+                }
+                self.network.rate_limit = value.to_owned();
+            }
+            "start" | "end" => {
+                let seconds = clip::parse_timestamp(value).ok_or_else(|| {
+                    format!("invalid time '{value}', expected seconds, MM:SS or HH:MM:SS")
+                })?;
+                if key == "start" {
+                    self.clip.start = Some(seconds);
+                } else {
+                    self.clip.end = Some(seconds);
+                }
+            }
+            "ignore-archive" => self.ignore_archive = parse_bool(value)?,
+            "split-chapters" => self.split.enabled = parse_bool(value)?,
+            _ => return Err(format!("unknown option '{key}'")),
+        }
+        Ok(())
+    }
+}
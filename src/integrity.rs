@@ -0,0 +1,326 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Checksum verification of the bundled executables.
+//!
+//! yt-dlp is checked against the SHA-256 sums published with its release, and the
+//! archive ffmpeg is extracted from against the sums published with its build. Every
+//! hash is recorded in a manifest next to the executables and re-checked at startup.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::config;
+use crate::fetcher::Binaries;
+use crate::network::Network;
+
+const MANIFEST: &str = "checksums.sha256";
+
+/// Release the ffmpeg builds are downloaded from, the ones yt-dlp recommends.
+pub const FFMPEG_RELEASE: &str = "https://github.com/BtbN/FFmpeg-Builds/releases/download/latest";
+
+/// Sums published with the ffmpeg builds.
+pub const FFMPEG_SUMS: &str = "checksums.sha256";
+
+/// Base URL of the yt-dlp releases, overridable with `COSMIC_YTDLP_RELEASES` to use a mirror.
+pub fn releases_url() -> String {
+    std::env::var("COSMIC_YTDLP_RELEASES")
+        .unwrap_or_else(|_| String::from("https://github.com/yt-dlp/yt-dlp/releases"))
+}
+
+pub async fn sha256(file: &Path) -> Result<String, String> {
+    let bytes = tokio::fs::read(file)
+        .await
+        .map_err(|err| format!("failed to read {}: {err}", file.display()))?;
+    Ok(format!("{:x}", Sha256::digest(bytes)))
+}
+
+/// Whether `sums`, in `sha256sum` format, list `hash`.
+fn listed(sums: &str, hash: &str) -> bool {
+    sums.lines()
+        .any(|line| line.split_whitespace().next() == Some(hash))
+}
+
+/// The sum `sums` list for the file `name`.
+fn sum_for<'a>(sums: &'a str, name: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (hash, file) = line.split_once(' ')?;
+        // `sha256sum` marks files read in binary mode with `*`
+        (file.trim_start().trim_start_matches('*') == name).then_some(hash)
+    })
+}
+
+async fn fetch_text(network: &Network, url: &str) -> Result<String, String> {
+    let client = network.client()?;
+    async {
+        client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
+    }
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Checks a yt-dlp executable against the sums published for `version`.
+pub async fn verify_published(file: &Path, version: &str, network: &Network) -> Result<(), String> {
+    verify_published_at(&releases_url(), file, version, network).await
+}
+
+async fn verify_published_at(
+    releases_url: &str,
+    file: &Path,
+    version: &str,
+    network: &Network,
+) -> Result<(), String> {
+    let hash = sha256(file).await?;
+    let url = format!("{releases_url}/download/{version}/SHA2-256SUMS");
+    let sums = fetch_text(network, &url)
+        .await
+        .map_err(|err| format!("failed to fetch yt-dlp {version} checksums: {err}"))?;
+    if listed(&sums, &hash) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} doesn't match any published checksum for yt-dlp {version}",
+            file.display()
+        ))
+    }
+}
+
+/// Checks a yt-dlp executable against a `SHA2-256SUMS` file from its release.
+pub async fn verify_sums(file: &Path, sums: &Path) -> Result<(), String> {
+    let hash = sha256(file).await?;
+    let sums = tokio::fs::read_to_string(sums)
+        .await
+        .map_err(|err| format!("failed to read {}: {err}", sums.display()))?;
+    if listed(&sums, &hash) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} doesn't match any checksum in its SHA2-256SUMS",
+            file.display()
+        ))
+    }
+}
+
+/// Checks the ffmpeg build archive named `name` against the sums published with it.
+pub async fn verify_ffmpeg(archive: &Path, name: &str, network: &Network) -> Result<(), String> {
+    verify_ffmpeg_at(FFMPEG_RELEASE, archive, name, network).await
+}
+
+async fn verify_ffmpeg_at(
+    release_url: &str,
+    archive: &Path,
+    name: &str,
+    network: &Network,
+) -> Result<(), String> {
+    let sums = fetch_text(network, &format!("{release_url}/{FFMPEG_SUMS}"))
+        .await
+        .map_err(|err| format!("failed to fetch ffmpeg checksums: {err}"))?;
+    verify_ffmpeg_sums(archive, name, &sums).await
+}
+
+/// Checks the ffmpeg build archive named `name` against its entry in `sums`. Builds
+/// without one are refused.
+pub async fn verify_ffmpeg_sums(archive: &Path, name: &str, sums: &str) -> Result<(), String> {
+    let expected =
+        sum_for(sums, name).ok_or_else(|| format!("no checksum is published for {name}"))?;
+    if sha256(archive).await? == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} doesn't match the published checksum of {name}",
+            archive.display()
+        ))
+    }
+}
+
+fn manifest_path(binaries: &Binaries) -> PathBuf {
+    binaries.yt_dlp.with_file_name(MANIFEST)
+}
+
+/// Reads the manifest as file name to hash, in `sha256sum` format.
+async fn manifest(binaries: &Binaries) -> BTreeMap<String, String> {
+    let contents = tokio::fs::read_to_string(manifest_path(binaries))
+        .await
+        .unwrap_or_default();
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (hash, name) = line.split_once("  ")?;
+            Some((name.to_owned(), hash.to_owned()))
+        })
+        .collect()
+}
+
+fn name(file: &Path) -> String {
+    file.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Records the current hashes of both executables.
+pub async fn record(binaries: &Binaries) -> Result<(), String> {
+    let mut manifest = manifest(binaries).await;
+    for file in [&binaries.yt_dlp, &binaries.ffmpeg] {
+        manifest.insert(name(file), sha256(file).await?);
+    }
+    let contents: String = manifest
+        .iter()
+        .map(|(name, hash)| format!("{hash}  {name}\n"))
+        .collect();
+    tokio::fs::write(manifest_path(binaries), contents)
+        .await
+        .map_err(|err| format!("failed to write checksums: {err}"))
+}
+
+/// Returns the executables that no longer match their recorded hash.
+pub async fn changed(binaries: &Binaries) -> Vec<PathBuf> {
+    let manifest = manifest(binaries).await;
+    let mut changed = vec![];
+    for file in [&binaries.yt_dlp, &binaries.ffmpeg] {
+        let Some(expected) = manifest.get(&name(file)) else {
+            continue;
+        };
+        if !file.exists() {
+            continue;
+        }
+        if sha256(file).await.ok().as_ref() != Some(expected) {
+            changed.push(file.clone());
+        }
+    }
+    changed
+}
+
+/// Moves an executable out of the way, so it is never run and gets installed again.
+/// Returns where it was moved.
+pub async fn quarantine(file: &Path) -> Result<PathBuf, String> {
+    let dir = file.with_file_name("quarantine");
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+    let target = dir.join(format!("{}.{}", name(file), config::now()));
+    tokio::fs::rename(file, &target)
+        .await
+        .map_err(|err| format!("failed to quarantine {}: {err}", file.display()))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Server;
+
+    const VERSION: &str = "2025.01.01";
+
+    /// A stand-in executable and its SHA-256 sum.
+    fn executable() -> (tempfile::NamedTempFile, String) {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"#!/bin/sh\necho 2025.01.01\n").unwrap();
+        let hash = format!("{:x}", Sha256::digest(b"#!/bin/sh\necho 2025.01.01\n"));
+        (file, hash)
+    }
+
+    async fn mirror(sums: &str) -> Server {
+        let path = format!("/download/{VERSION}/SHA2-256SUMS");
+        Server::start(&[(path.as_str(), 200, sums.as_bytes())]).await
+    }
+
+    #[tokio::test]
+    async fn yt_dlp_is_checked_against_a_mirror() {
+        let (file, hash) = executable();
+        let network = Network::default();
+
+        let server = mirror(&format!("0123  yt-dlp.exe\n{hash}  yt-dlp\n")).await;
+        verify_published_at(&server.url, file.path(), VERSION, &network)
+            .await
+            .unwrap();
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].target,
+            format!("/download/{VERSION}/SHA2-256SUMS")
+        );
+
+        let server = mirror("0123  yt-dlp\n").await;
+        let res = verify_published_at(&server.url, file.path(), VERSION, &network).await;
+        assert!(res.unwrap_err().contains("doesn't match"));
+
+        let res = verify_published_at(&server.url, file.path(), "2024.01.01", &network).await;
+        assert!(res.unwrap_err().contains("failed to fetch"));
+    }
+
+    #[tokio::test]
+    async fn yt_dlp_is_checked_against_a_local_sums_file() {
+        let (file, hash) = executable();
+        let sums = tempfile::NamedTempFile::new().unwrap();
+
+        std::fs::write(sums.path(), format!("{hash}  yt-dlp\n")).unwrap();
+        verify_sums(file.path(), sums.path()).await.unwrap();
+
+        std::fs::write(sums.path(), "0123  yt-dlp\n").unwrap();
+        assert!(verify_sums(file.path(), sums.path()).await.is_err());
+    }
+
+    #[test]
+    fn sums_are_found_by_file_name() {
+        let sums = "0123  ffmpeg-master-latest-linux64-gpl.tar.xz\n\
+            4567 *ffmpeg-master-latest-linuxarm64-gpl.tar.xz\n";
+        assert_eq!(
+            sum_for(sums, "ffmpeg-master-latest-linux64-gpl.tar.xz"),
+            Some("0123")
+        );
+        assert_eq!(
+            sum_for(sums, "ffmpeg-master-latest-linuxarm64-gpl.tar.xz"),
+            Some("4567")
+        );
+        assert_eq!(sum_for(sums, "ffmpeg-master-latest-linux64-gpl"), None);
+    }
+
+    #[tokio::test]
+    async fn ffmpeg_is_checked_against_the_published_sums() {
+        let (file, hash) = executable();
+        let network = Network::default();
+        let name = "ffmpeg-master-latest-linux64-gpl.tar.xz";
+
+        let sums = format!("0123  ffmpeg-master-latest-win64-gpl.zip\n{hash}  {name}\n");
+        let server = Server::start(&[("/checksums.sha256", 200, sums.as_bytes())]).await;
+        verify_ffmpeg_at(&server.url, file.path(), name, &network)
+            .await
+            .unwrap();
+
+        let sums = format!("0123  {name}\n");
+        let server = Server::start(&[("/checksums.sha256", 200, sums.as_bytes())]).await;
+        let res = verify_ffmpeg_at(&server.url, file.path(), name, &network).await;
+        assert!(res.unwrap_err().contains("doesn't match"));
+
+        // A build without a published sum isn't trusted
+        let sums = format!("{hash}  ffmpeg-master-latest-win64-gpl.zip\n");
+        let server = Server::start(&[("/checksums.sha256", 200, sums.as_bytes())]).await;
+        let res = verify_ffmpeg_at(&server.url, file.path(), name, &network).await;
+        assert!(res.unwrap_err().contains("no checksum"));
+
+        let server = Server::start(&[]).await;
+        let res = verify_ffmpeg_at(&server.url, file.path(), name, &network).await;
+        assert!(res.unwrap_err().contains("failed to fetch"));
+    }
+
+    #[tokio::test]
+    async fn quarantine_says_where_the_file_went() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("yt-dlp");
+        std::fs::write(&file, b"changed").unwrap();
+
+        let target = quarantine(&file).await.unwrap();
+        assert!(!file.exists());
+        assert_eq!(
+            target.parent(),
+            Some(dir.path().join("quarantine").as_path())
+        );
+        assert_eq!(std::fs::read(&target).unwrap(), b"changed");
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+use tokio::task::AbortHandle;
+
+/// Finished jobs kept for listing, older ones being dropped first.
+const KEPT_FINISHED: usize = 100;
+
+/// Lifecycle of a download job.
+///
+/// Jobs move forward one state at a time through `Queued`, `FetchingMetadata`,
+/// `Downloading` and `PostProcessing`, and end in `Done`, `Failed`, `Cancelled` or
+/// `Skipped`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JobState {
+    Queued,
+    FetchingMetadata,
+    Downloading,
+    PostProcessing,
+    Done,
+    Failed,
+    Cancelled,
+    /// Already in the download archive.
+    Skipped,
+}
+
+impl JobState {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobState::Done | JobState::Failed | JobState::Cancelled | JobState::Skipped
+        )
+    }
+
+    /// Whether a job may move from `self` to `next`. Jobs only succeed once
+    /// post-processed and are only skipped once their metadata is known, but may
+    /// fail or be cancelled at any point.
+    pub fn can_advance_to(self, next: JobState) -> bool {
+        match (self, next) {
+            (JobState::Queued, JobState::FetchingMetadata)
+            | (JobState::FetchingMetadata, JobState::Downloading | JobState::Skipped)
+            | (JobState::Downloading, JobState::PostProcessing)
+            | (JobState::PostProcessing, JobState::Done) => true,
+            (_, JobState::Failed | JobState::Cancelled) => !self.is_finished(),
+            _ => false,
+        }
+    }
+}
+
+impl Display for JobState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobState::Queued => write!(f, "queued"),
+            JobState::FetchingMetadata => write!(f, "fetching-metadata"),
+            JobState::Downloading => write!(f, "downloading"),
+            JobState::PostProcessing => write!(f, "post-processing"),
+            JobState::Done => write!(f, "done"),
+            JobState::Failed => write!(f, "failed"),
+            JobState::Cancelled => write!(f, "cancelled"),
+            JobState::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub url: String,
+    pub title: String,
+    pub state: JobState,
+    /// Why the job failed or was skipped, or what went wrong without stopping it.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    next_id: u32,
+    jobs: BTreeMap<u32, JobInfo>,
+    handles: HashMap<u32, AbortHandle>,
+}
+
+impl Registry {
+    /// Drops the oldest finished jobs beyond [`KEPT_FINISHED`].
+    fn prune(&mut self) {
+        let finished: Vec<u32> = self
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.state.is_finished())
+            .map(|(id, _)| *id)
+            .collect();
+        let excess = finished.len().saturating_sub(KEPT_FINISHED);
+        for id in &finished[..excess] {
+            self.jobs.remove(id);
+        }
+    }
+}
+
+/// Job registry shared between the download service and its front-ends.
+#[derive(Debug, Clone, Default)]
+pub struct Jobs(Arc<Mutex<Registry>>);
+
+impl Jobs {
+    pub fn insert(&self, url: String) -> u32 {
+        let mut registry = self.0.lock().unwrap();
+        registry.next_id += 1;
+        let id = registry.next_id;
+        registry.jobs.insert(
+            id,
+            JobInfo {
+                title: url.clone(),
+                url,
+                state: JobState::Queued,
+                error: None,
+            },
+        );
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Option<JobInfo> {
+        self.0.lock().unwrap().jobs.get(&id).cloned()
+    }
+
+    pub fn set_title(&self, id: u32, title: String) {
+        if let Some(job) = self.0.lock().unwrap().jobs.get_mut(&id) {
+            job.title = title;
+        }
+    }
+
+    /// Moves a job to `state`, returning the updated job if the transition is allowed.
+    pub fn advance(&self, id: u32, state: JobState, error: Option<String>) -> Option<JobInfo> {
+        let mut registry = self.0.lock().unwrap();
+        let job = registry.jobs.get_mut(&id)?;
+        if !job.state.can_advance_to(state) {
+            return None;
+        }
+        job.state = state;
+        job.error = error;
+        let job = job.clone();
+        if state.is_finished() {
+            registry.handles.remove(&id);
+            registry.prune();
+        }
+        Some(job)
+    }
+
+    pub fn set_handle(&self, id: u32, handle: AbortHandle) {
+        let mut registry = self.0.lock().unwrap();
+        // The job may already be over by the time its handle arrives
+        if registry
+            .jobs
+            .get(&id)
+            .is_some_and(|job| !job.state.is_finished())
+        {
+            registry.handles.insert(id, handle);
+        }
+    }
+
+    pub fn take_handle(&self, id: u32) -> Option<AbortHandle> {
+        self.0.lock().unwrap().handles.remove(&id)
+    }
+
+    pub fn list(&self) -> Vec<(u32, JobInfo)> {
+        let registry = self.0.lock().unwrap();
+        registry
+            .jobs
+            .iter()
+            .map(|(id, job)| (*id, job.clone()))
+            .collect()
+    }
+
+    /// Number of jobs that haven't finished yet.
+    pub fn active(&self) -> usize {
+        let registry = self.0.lock().unwrap();
+        registry
+            .jobs
+            .values()
+            .filter(|job| !job.state.is_finished())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [JobState; 8] = [
+        JobState::Queued,
+        JobState::FetchingMetadata,
+        JobState::Downloading,
+        JobState::PostProcessing,
+        JobState::Done,
+        JobState::Failed,
+        JobState::Cancelled,
+        JobState::Skipped,
+    ];
+
+    #[test]
+    fn states_advance_one_step_at_a_time() {
+        let path = [
+            JobState::Queued,
+            JobState::FetchingMetadata,
+            JobState::Downloading,
+            JobState::PostProcessing,
+            JobState::Done,
+        ];
+        for pair in path.windows(2) {
+            assert!(
+                pair[0].can_advance_to(pair[1]),
+                "{} -> {}",
+                pair[0],
+                pair[1]
+            );
+        }
+        assert!(!JobState::Queued.can_advance_to(JobState::Downloading));
+        assert!(!JobState::FetchingMetadata.can_advance_to(JobState::PostProcessing));
+        assert!(!JobState::Queued.can_advance_to(JobState::Done));
+        assert!(!JobState::Downloading.can_advance_to(JobState::FetchingMetadata));
+    }
+
+    #[test]
+    fn only_fetched_jobs_are_skipped() {
+        for state in ALL {
+            let allowed = state == JobState::FetchingMetadata;
+            assert_eq!(state.can_advance_to(JobState::Skipped), allowed, "{state}");
+        }
+    }
+
+    #[test]
+    fn active_jobs_can_fail_or_be_cancelled() {
+        for state in ALL {
+            for end in [JobState::Failed, JobState::Cancelled] {
+                assert_eq!(state.can_advance_to(end), !state.is_finished(), "{state}");
+            }
+        }
+    }
+
+    #[test]
+    fn finished_jobs_stay_finished() {
+        for state in ALL.into_iter().filter(|state| state.is_finished()) {
+            assert!(
+                ALL.iter().all(|next| !state.can_advance_to(*next)),
+                "{state}"
+            );
+        }
+    }
+
+    #[test]
+    fn finished_jobs_are_pruned_oldest_first() {
+        let jobs = Jobs::default();
+        let running = jobs.insert(String::from("https://example.com/running"));
+        let finished: Vec<u32> = (0..KEPT_FINISHED + 5)
+            .map(|i| {
+                let id = jobs.insert(format!("https://example.com/{i}"));
+                jobs.advance(id, JobState::Failed, None);
+                id
+            })
+            .collect();
+
+        assert_eq!(jobs.list().len(), KEPT_FINISHED + 1);
+        assert!(jobs.get(running).is_some());
+        assert!(finished[..5].iter().all(|id| jobs.get(*id).is_none()));
+        assert!(finished[5..].iter().all(|id| jobs.get(*id).is_some()));
+    }
+}
@@ -1,9 +1,35 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 mod applet;
+mod archive;
+mod auth;
+mod batch;
+mod cli;
+mod clip;
+mod clipboard;
+mod config;
+mod dbus;
+mod download;
 mod fetcher;
+mod folders;
 mod formats;
 mod i18n;
+mod integrity;
+mod jobs;
+mod network;
+mod notifications;
+mod power;
+mod presets;
+mod rules;
+mod schedule;
+mod split;
+mod sponsorblock;
+mod subscriptions;
+#[cfg(test)]
+mod testing;
+mod urls;
+
+use clap::Parser;
 
 #[tokio::main]
 async fn main() -> cosmic::iced::Result {
@@ -13,5 +39,10 @@ async fn main() -> cosmic::iced::Result {
     // Enable localizations to be applied.
     i18n::init(&requested_languages);
 
-    cosmic::applet::run::<applet::Ytdlp>(fetcher::binaries().await)
+    // Run headless when given a subcommand
+    if let Some(command) = cli::Cli::parse().command {
+        std::process::exit(cli::run(command).await);
+    }
+
+    cosmic::applet::run::<applet::Ytdlp>(())
 }
@@ -0,0 +1,357 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fmt::Display;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use crate::fl;
+
+const DEFAULT_TIMEOUT: u64 = 30;
+const DEFAULT_RETRIES: u32 = 10;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IpVersion {
+    #[default]
+    Any,
+    V4,
+    V6,
+}
+
+impl IpVersion {
+    pub const ALL: [Self; 3] = [Self::Any, Self::V4, Self::V6];
+}
+
+impl Display for IpVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpVersion::Any => write!(f, "{}", fl!("ip-any")),
+            IpVersion::V4 => write!(f, "IPv4"),
+            IpVersion::V6 => write!(f, "IPv6"),
+        }
+    }
+}
+
+/// Network settings shared by yt-dlp and the native segment fetcher.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Network {
+    /// HTTP, HTTPS or SOCKS proxy, e.g. `socks5://127.0.0.1:1080`.
+    pub proxy: String,
+    /// Bandwidth limit in bytes per second, with an optional K, M or G suffix as in yt-dlp.
+    pub rate_limit: String,
+    /// Local address to connect from.
+    pub source_address: String,
+    pub ip_version: IpVersion,
+    /// Timeout in seconds.
+    pub timeout: Option<u64>,
+    pub retries: Option<u32>,
+}
+
+impl Network {
+    /// Whether anything differs from the defaults.
+    pub fn is_set(&self) -> bool {
+        self != &Self::default()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_TIMEOUT))
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(DEFAULT_RETRIES)
+    }
+
+    /// Parses the bandwidth limit into bytes per second.
+    pub fn rate_limit(&self) -> Option<u64> {
+        parse_rate_limit(&self.rate_limit)
+    }
+
+    fn source_address(&self) -> Option<IpAddr> {
+        self.source_address.trim().parse().ok()
+    }
+
+    /// Arguments that make yt-dlp follow these settings.
+    pub fn ytdlp_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        let proxy = self.proxy.trim();
+        if !proxy.is_empty() {
+            args.extend([String::from("--proxy"), proxy.to_owned()]);
+        }
+        if let Some(limit) = self.rate_limit() {
+            args.extend([String::from("--limit-rate"), limit.to_string()]);
+        }
+        if let Some(address) = self.source_address() {
+            args.extend([String::from("--source-address"), address.to_string()]);
+        }
+        match self.ip_version {
+            IpVersion::Any => {}
+            IpVersion::V4 => args.push(String::from("--force-ipv4")),
+            IpVersion::V6 => args.push(String::from("--force-ipv6")),
+        }
+        let timeout = self.timeout().as_secs().to_string();
+        let retries = self.retries().to_string();
+        args.extend([
+            String::from("--socket-timeout"),
+            timeout,
+            String::from("--retries"),
+            retries.clone(),
+            String::from("--fragment-retries"),
+            retries,
+        ]);
+        args
+    }
+
+    /// An HTTP client that follows these settings.
+    pub fn client(&self) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder().connect_timeout(self.timeout());
+        let proxy = self.proxy.trim();
+        if !proxy.is_empty() {
+            builder = builder.proxy(parse_proxy(proxy)?);
+        }
+        // Binding to an unspecified address of one family forces that family
+        let address = self.source_address().or(match self.ip_version {
+            IpVersion::Any => None,
+            IpVersion::V4 => Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            IpVersion::V6 => Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        });
+        builder
+            .local_address(address)
+            .read_timeout(self.timeout())
+            .build()
+            .map_err(|err| format!("failed to create HTTP client: {err}"))
+    }
+
+    /// Downloads a single file with a client that follows these settings.
+    pub async fn download(&self, url: &str, file: &Path, headers: HeaderMap) -> Result<(), String> {
+        let throttle = Throttle::new(self.rate_limit());
+        let client = self.client()?;
+        fetch(&client, &throttle, self.retries(), url, &headers, file).await
+    }
+}
+
+/// Parses a bandwidth limit into bytes per second, `None` if it's unset or malformed.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn parse_rate_limit(limit: &str) -> Option<u64> {
+    let limit = limit.trim();
+    let (number, multiplier) = match limit.char_indices().last()? {
+        (i, 'k' | 'K') => (&limit[..i], 1 << 10),
+        (i, 'm' | 'M') => (&limit[..i], 1 << 20),
+        (i, 'g' | 'G') => (&limit[..i], 1 << 30),
+        _ => (limit, 1),
+    };
+    let bytes = number.trim().parse::<f64>().ok()? * f64::from(multiplier);
+    // Only the fraction is dropped, it's at least 1 and casts saturate at `u64::MAX`
+    (bytes >= 1.0).then_some(bytes as u64)
+}
+
+pub fn parse_proxy(proxy: &str) -> Result<reqwest::Proxy, String> {
+    reqwest::Proxy::all(proxy.trim()).map_err(|err| format!("invalid proxy '{proxy}': {err}"))
+}
+
+/// Keeps the combined rate of several transfers under a limit.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    limit: Option<u64>,
+    progress: Arc<Mutex<(Instant, u64)>>,
+}
+
+impl Throttle {
+    pub fn new(limit: Option<u64>) -> Self {
+        Self {
+            limit,
+            progress: Arc::new(Mutex::new((Instant::now(), 0))),
+        }
+    }
+
+    async fn consume(&self, bytes: usize) {
+        let Some(limit) = self.limit.filter(|limit| *limit > 0) else {
+            return;
+        };
+        let wait = {
+            let mut progress = self.progress.lock().unwrap();
+            progress.1 = progress
+                .1
+                .saturating_add(u64::try_from(bytes).unwrap_or(u64::MAX));
+            let nanos = u128::from(progress.1) * 1_000_000_000 / u128::from(limit);
+            let expected = Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX));
+            expected.saturating_sub(progress.0.elapsed())
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Downloads `url` to `file` with the given request headers, retrying attempts that
+/// failed to connect, were cut off or met a server error.
+pub async fn fetch(
+    client: &reqwest::Client,
+    throttle: &Throttle,
+    retries: u32,
+    url: &str,
+    headers: &HeaderMap,
+    file: &Path,
+) -> Result<(), String> {
+    let mut attempt = 0;
+    loop {
+        match fetch_once(client, throttle, url, headers, file).await {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < retries && retryable(err.as_ref()) => attempt += 1,
+            Err(err) => return Err(format!("failed to fetch {url}: {err}")),
+        }
+    }
+}
+
+/// Whether trying again may work. Errors writing the file and HTTP errors other
+/// than server errors and rate limiting would fail the same way again.
+fn retryable(err: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    let Some(err) = err.downcast_ref::<reqwest::Error>() else {
+        return false;
+    };
+    match err.status() {
+        Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        None => err.is_connect() || err.is_timeout() || err.is_request() || err.is_body(),
+    }
+}
+
+async fn fetch_once(
+    client: &reqwest::Client,
+    throttle: &Throttle,
+    url: &str,
+    headers: &HeaderMap,
+    file: &Path,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let request = client.get(url).headers(headers.clone());
+    let mut response = request.send().await?.error_for_status()?;
+    let mut out = tokio::fs::File::create(file).await?;
+    while let Some(chunk) = response.chunk().await? {
+        out.write_all(&chunk).await?;
+        throttle.consume(chunk.len()).await;
+    }
+    out.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Server;
+
+    fn limit(rate_limit: &str) -> Option<u64> {
+        Network {
+            rate_limit: rate_limit.to_owned(),
+            ..Network::default()
+        }
+        .rate_limit()
+    }
+
+    #[test]
+    fn rate_limits_take_binary_suffixes() {
+        assert_eq!(limit("500"), Some(500));
+        assert_eq!(limit(" 50k "), Some(50 << 10));
+        assert_eq!(limit("1.5M"), Some(3 << 19));
+        assert_eq!(limit("2 G"), Some(2 << 30));
+    }
+
+    #[test]
+    fn unusable_rate_limits_are_ignored() {
+        for rate_limit in ["", "M", "fast", "0", "0.5", "-1K", "10T"] {
+            assert_eq!(limit(rate_limit), None, "{rate_limit}");
+        }
+        assert_eq!(limit("1e30G"), Some(u64::MAX));
+    }
+
+    #[test]
+    fn proxies_need_a_url() {
+        assert!(parse_proxy("socks5://127.0.0.1:1080").is_ok());
+        assert!(parse_proxy(" http://proxy.example.com:3128 ").is_ok());
+        assert!(parse_proxy("not a proxy").is_err());
+    }
+
+    #[test]
+    fn limits_are_passed_to_ytdlp_in_bytes() {
+        let network = Network {
+            rate_limit: String::from("2K"),
+            ..Network::default()
+        };
+        let args = network.ytdlp_args();
+        let i = args.iter().position(|arg| arg == "--limit-rate").unwrap();
+        assert_eq!(args[i + 1], "2048");
+    }
+
+    #[tokio::test]
+    async fn throttles_wait_for_the_combined_rate() {
+        let throttle = Throttle::new(Some(10_000));
+        let other = throttle.clone();
+        let started = Instant::now();
+        // 2 KB at 10 KB/s, split between two transfers sharing the limit
+        throttle.consume(1000).await;
+        other.consume(1000).await;
+        assert!(started.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[tokio::test]
+    async fn unlimited_throttles_never_wait() {
+        let started = Instant::now();
+        Throttle::new(None).consume(usize::MAX).await;
+        Throttle::new(Some(0)).consume(usize::MAX).await;
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+
+    async fn attempts(status: u16) -> (Result<(), String>, usize) {
+        let server = Server::start(&[("/file", status, b"body")]).await;
+        let dir = tempfile::tempdir().unwrap();
+        let result = fetch(
+            &reqwest::Client::new(),
+            &Throttle::new(None),
+            2,
+            &format!("{}/file", server.url),
+            &HeaderMap::new(),
+            &dir.path().join("file"),
+        )
+        .await;
+        (result, server.requests().len())
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried() {
+        for status in [500, 503, 429] {
+            let (result, requests) = attempts(status).await;
+            assert!(result.is_err(), "{status}");
+            assert_eq!(requests, 3, "{status}");
+        }
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        for status in [403, 404, 410] {
+            let (result, requests) = attempts(status).await;
+            assert!(result.is_err(), "{status}");
+            assert_eq!(requests, 1, "{status}");
+        }
+    }
+
+    #[tokio::test]
+    async fn successful_fetches_are_written() {
+        let server = Server::start(&[("/file", 200, b"body")]).await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        let url = format!("{}/file", server.url);
+        fetch(
+            &reqwest::Client::new(),
+            &Throttle::new(None),
+            2,
+            &url,
+            &HeaderMap::new(),
+            &file,
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(file).unwrap(), b"body");
+        assert_eq!(server.requests().len(), 1);
+    }
+}
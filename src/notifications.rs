@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Notifications that ask the user something.
+//!
+//! notify-rust only waits for actions on a blocking call, holding a thread for as long
+//! as the notification is open, so these talk to the notification server directly and
+//! wait for its signals instead.
+
+use std::collections::HashMap;
+use std::future::ready;
+use std::pin::pin;
+
+use cosmic::iced::futures::StreamExt;
+use cosmic::iced::futures::stream::select;
+use zbus::zvariant::Value;
+use zbus::{Connection, proxy};
+
+pub const APP_NAME: &str = "yt-dlp applet";
+pub const ICON: &str = "multimedia-video-player-symbolic";
+
+/// Key of the single action offered.
+const ACCEPT: &str = "accept";
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: &str) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+/// Shows a notification with a single action, returning whether it was clicked
+/// before the notification closed.
+pub async fn ask(summary: &str, body: &str, action: &str) -> bool {
+    answer(summary, body, action).await.unwrap_or_default()
+}
+
+async fn answer(summary: &str, body: &str, action: &str) -> zbus::Result<bool> {
+    let connection = Connection::session().await?;
+    let notifications = NotificationsProxy::new(&connection).await?;
+    // Listening before showing it, so a quick answer isn't missed
+    let invoked = notifications.receive_action_invoked().await?;
+    let closed = notifications.receive_notification_closed().await?;
+    let id = notifications
+        .notify(
+            APP_NAME,
+            0,
+            ICON,
+            summary,
+            body,
+            &[ACCEPT, action],
+            HashMap::new(),
+            -1,
+        )
+        .await?;
+    let invoked = invoked.filter_map(|signal| {
+        let answer = signal.args().ok().filter(|args| args.id == id);
+        ready(answer.map(|args| args.action_key == ACCEPT))
+    });
+    let closed = closed.filter_map(|signal| {
+        let closed = signal.args().is_ok_and(|args| args.id == id);
+        ready(closed.then_some(false))
+    });
+    let mut answers = pin!(select(invoked, closed));
+    Ok(answers.next().await.unwrap_or_default())
+}
@@ -0,0 +1,417 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Deferring downloads on metered connections and on battery.
+//!
+//! The metered state comes from NetworkManager and the battery state from UPower, both
+//! on the system bus. `COSMIC_YTDLP_POWER_BUS` points to another bus instead, e.g. one
+//! with stand-in services for testing.
+
+use std::fmt::Display;
+
+use cosmic::iced::futures::stream::{BoxStream, select_all};
+use cosmic::iced::futures::{SinkExt, StreamExt};
+use cosmic::iced::{Subscription, stream};
+use serde::{Deserialize, Serialize};
+use zbus::{Connection, proxy};
+
+use crate::fl;
+
+/// NetworkManager's `NM_METERED_YES` and `NM_METERED_GUESS_YES`.
+const METERED: [u32; 2] = [1, 3];
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager"
+)]
+trait NetworkManager {
+    #[zbus(property)]
+    fn metered(&self) -> zbus::Result<u32>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower"
+)]
+trait UPower {
+    #[zbus(property)]
+    fn on_battery(&self) -> zbus::Result<bool>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.UPower.Device",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower/devices/DisplayDevice"
+)]
+trait Device {
+    #[zbus(property)]
+    fn percentage(&self) -> zbus::Result<f64>;
+}
+
+/// What to do with downloads while a condition holds.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Policy {
+    #[default]
+    Ignore,
+    /// Keep new jobs waiting.
+    Defer,
+    /// Keep new jobs waiting and stop running ones, to restart them from the
+    /// beginning later. Partly downloaded files are not kept.
+    Pause,
+}
+
+impl Policy {
+    pub const ALL: [Self; 3] = [Self::Ignore, Self::Defer, Self::Pause];
+}
+
+impl Display for Policy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Policy::Ignore => write!(f, "{}", fl!("policy-ignore")),
+            Policy::Defer => write!(f, "{}", fl!("policy-defer")),
+            Policy::Pause => write!(f, "{}", fl!("policy-pause")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerPolicy {
+    pub metered: Policy,
+    pub battery: Policy,
+    /// Only apply the battery policy below this charge percentage.
+    pub battery_below: Option<u8>,
+}
+
+/// Current network and power state.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Conditions {
+    pub metered: bool,
+    pub on_battery: bool,
+    pub battery: Option<f64>,
+}
+
+/// Why downloads are held back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Metered,
+    Battery,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Metered => write!(f, "{}", fl!("held-metered")),
+            Reason::Battery => write!(f, "{}", fl!("held-battery")),
+        }
+    }
+}
+
+impl PowerPolicy {
+    pub fn is_set(&self) -> bool {
+        self.metered != Policy::Ignore || self.battery != Policy::Ignore
+    }
+
+    fn low_battery(&self, conditions: &Conditions) -> bool {
+        conditions.on_battery
+            && self.battery_below.is_none_or(|below| {
+                conditions
+                    .battery
+                    .is_none_or(|percentage| percentage < f64::from(below))
+            })
+    }
+
+    /// The strictest policy that applies, and why.
+    pub fn action(&self, conditions: &Conditions) -> (Policy, Option<Reason>) {
+        let mut action = (Policy::Ignore, None);
+        if conditions.metered && self.metered > action.0 {
+            action = (self.metered, Some(Reason::Metered));
+        }
+        if self.low_battery(conditions) && self.battery > action.0 {
+            action = (self.battery, Some(Reason::Battery));
+        }
+        action
+    }
+}
+
+async fn connect() -> zbus::Result<Connection> {
+    match std::env::var("COSMIC_YTDLP_POWER_BUS") {
+        Ok(address) => {
+            zbus::connection::Builder::address(address.as_str())?
+                .build()
+                .await
+        }
+        Err(_) => Connection::system().await,
+    }
+}
+
+async fn proxies(
+    connection: &Connection,
+) -> zbus::Result<(
+    NetworkManagerProxy<'static>,
+    UPowerProxy<'static>,
+    DeviceProxy<'static>,
+)> {
+    Ok((
+        NetworkManagerProxy::new(connection).await?,
+        UPowerProxy::new(connection).await?,
+        DeviceProxy::new(connection).await?,
+    ))
+}
+
+/// Reads the current state, treating missing services as unmetered and on AC.
+async fn read(
+    network: &NetworkManagerProxy<'_>,
+    upower: &UPowerProxy<'_>,
+    device: &DeviceProxy<'_>,
+) -> Conditions {
+    Conditions {
+        metered: network
+            .metered()
+            .await
+            .is_ok_and(|metered| METERED.contains(&metered)),
+        on_battery: upower.on_battery().await.unwrap_or_default(),
+        battery: device.percentage().await.ok(),
+    }
+}
+
+/// Reports the conditions on start and whenever they change.
+pub fn subscription() -> Subscription<Conditions> {
+    Subscription::run_with_id(
+        "power-conditions",
+        stream::channel(4, |mut output| async move {
+            let proxies = async { proxies(&connect().await?).await };
+            match proxies.await {
+                Ok((network, upower, device)) => {
+                    let changes: Vec<BoxStream<'_, ()>> = vec![
+                        network.receive_metered_changed().await.map(|_| ()).boxed(),
+                        upower
+                            .receive_on_battery_changed()
+                            .await
+                            .map(|_| ())
+                            .boxed(),
+                        device
+                            .receive_percentage_changed()
+                            .await
+                            .map(|_| ())
+                            .boxed(),
+                    ];
+                    let mut changes = select_all(changes);
+                    let mut last = read(&network, &upower, &device).await;
+                    let _ = output.send(last).await;
+                    while changes.next().await.is_some() {
+                        let conditions = read(&network, &upower, &device).await;
+                        if conditions != last {
+                            last = conditions;
+                            let _ = output.send(conditions).await;
+                        }
+                    }
+                }
+                Err(err) => eprintln!("failed to watch network and power state: {err}"),
+            }
+            std::future::pending::<()>().await;
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Stdio;
+
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::process::{Child, Command};
+    use zbus::interface;
+
+    use super::*;
+
+    fn conditions(metered: bool, on_battery: bool, battery: Option<f64>) -> Conditions {
+        Conditions {
+            metered,
+            on_battery,
+            battery,
+        }
+    }
+
+    #[test]
+    fn battery_is_low_below_the_threshold() {
+        let mut policy = PowerPolicy::default();
+        assert!(!policy.low_battery(&conditions(false, false, Some(5.0))));
+        assert!(policy.low_battery(&conditions(false, true, Some(90.0))));
+
+        policy.battery_below = Some(20);
+        assert!(!policy.low_battery(&conditions(false, true, Some(50.0))));
+        assert!(!policy.low_battery(&conditions(false, true, Some(20.0))));
+        assert!(policy.low_battery(&conditions(false, true, Some(19.5))));
+        assert!(policy.low_battery(&conditions(false, true, None)));
+        assert!(!policy.low_battery(&conditions(false, false, None)));
+    }
+
+    #[test]
+    fn strictest_policy_applies() {
+        let policy = PowerPolicy {
+            metered: Policy::Defer,
+            battery: Policy::Pause,
+            battery_below: None,
+        };
+        assert_eq!(
+            policy.action(&conditions(false, false, None)),
+            (Policy::Ignore, None)
+        );
+        assert_eq!(
+            policy.action(&conditions(true, false, None)),
+            (Policy::Defer, Some(Reason::Metered))
+        );
+        assert_eq!(
+            policy.action(&conditions(false, true, None)),
+            (Policy::Pause, Some(Reason::Battery))
+        );
+        assert_eq!(
+            policy.action(&conditions(true, true, None)),
+            (Policy::Pause, Some(Reason::Battery))
+        );
+
+        // Equally strict policies are put down to the network
+        let policy = PowerPolicy {
+            battery: Policy::Defer,
+            ..policy
+        };
+        assert_eq!(
+            policy.action(&conditions(true, true, None)),
+            (Policy::Defer, Some(Reason::Metered))
+        );
+    }
+
+    #[test]
+    fn ignored_conditions_hold_nothing_back() {
+        let policy = PowerPolicy::default();
+        assert!(!policy.is_set());
+        assert_eq!(
+            policy.action(&conditions(true, true, Some(1.0))),
+            (Policy::Ignore, None)
+        );
+    }
+
+    struct NetworkManager {
+        metered: u32,
+    }
+
+    #[interface(name = "org.freedesktop.NetworkManager")]
+    impl NetworkManager {
+        #[zbus(property)]
+        fn metered(&self) -> u32 {
+            self.metered
+        }
+    }
+
+    struct UPower {
+        on_battery: bool,
+    }
+
+    #[interface(name = "org.freedesktop.UPower")]
+    impl UPower {
+        #[zbus(property)]
+        fn on_battery(&self) -> bool {
+            self.on_battery
+        }
+    }
+
+    struct Device {
+        percentage: f64,
+    }
+
+    #[interface(name = "org.freedesktop.UPower.Device")]
+    impl Device {
+        #[zbus(property)]
+        fn percentage(&self) -> f64 {
+            self.percentage
+        }
+    }
+
+    /// Starts a private bus, returning the daemon and the bus address.
+    async fn private_bus() -> (Child, String) {
+        let config = concat!(
+            "<busconfig><type>session</type><listen>unix:tmpdir=/tmp</listen>",
+            "<auth>EXTERNAL</auth><policy context=\"default\"><allow send_destination=\"*\"/>",
+            "<allow own=\"*\"/></policy></busconfig>",
+        );
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, config.as_bytes()).unwrap();
+        let mut daemon = Command::new("dbus-daemon")
+            .arg(format!("--config-file={}", file.path().display()))
+            .args(["--nofork", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .expect("dbus-daemon should be installed");
+        let mut address = String::new();
+        let stdout = daemon.stdout.take().unwrap();
+        BufReader::new(stdout)
+            .read_line(&mut address)
+            .await
+            .unwrap();
+        (daemon, address.trim().to_owned())
+    }
+
+    #[tokio::test]
+    #[ignore = "needs dbus-daemon, run with `cargo test -- --ignored`"]
+    async fn conditions_come_from_networkmanager_and_upower() {
+        let (_daemon, address) = private_bus().await;
+        let connect = || async {
+            zbus::connection::Builder::address(address.as_str())
+                .unwrap()
+                .build()
+                .await
+                .unwrap()
+        };
+        let services = connect().await;
+        let server = services.object_server();
+        server
+            .at(
+                "/org/freedesktop/NetworkManager",
+                NetworkManager { metered: 4 },
+            )
+            .await
+            .unwrap();
+        server
+            .at("/org/freedesktop/UPower", UPower { on_battery: true })
+            .await
+            .unwrap();
+        let device = Device { percentage: 42.0 };
+        server
+            .at("/org/freedesktop/UPower/devices/DisplayDevice", device)
+            .await
+            .unwrap();
+        services
+            .request_name("org.freedesktop.NetworkManager")
+            .await
+            .unwrap();
+        services
+            .request_name("org.freedesktop.UPower")
+            .await
+            .unwrap();
+
+        let client = connect().await;
+        let (network, upower, device) = proxies(&client).await.unwrap();
+        assert_eq!(
+            read(&network, &upower, &device).await,
+            conditions(false, true, Some(42.0))
+        );
+
+        // Changes are picked up as the services announce them
+        let mut changes = network.receive_metered_changed().await;
+        let metered = server
+            .interface::<_, NetworkManager>("/org/freedesktop/NetworkManager")
+            .await
+            .unwrap();
+        metered.get_mut().await.metered = 1;
+        let emitter = metered.signal_emitter();
+        metered.get().await.metered_changed(emitter).await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), changes.next())
+            .await
+            .unwrap();
+        assert_eq!(
+            read(&network, &upower, &device).await,
+            conditions(true, true, Some(42.0))
+        );
+    }
+}
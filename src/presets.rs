@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use serde::{Deserialize, Serialize};
+
+use crate::folders;
+use crate::formats::{AudioCodec, AudioQuality, DownloadOptions, VideoCodec, VideoQuality};
+
+/// A named set of download options.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub video: bool,
+    pub video_quality: VideoQuality,
+    pub video_codec: VideoCodec,
+    pub audio_quality: AudioQuality,
+    pub audio_codec: AudioCodec,
+    /// Output folder, empty for the default folder of the download type.
+    pub folder: String,
+    /// yt-dlp output template for the file name, e.g. `%(uploader)s - %(title)s`.
+    pub filename_template: String,
+    /// Extra yt-dlp arguments, quoted like in a shell.
+    pub extra_args: String,
+}
+
+impl Preset {
+    /// Presets that ship with the applet.
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self {
+                name: String::from("Podcast MP3 128k"),
+                video: false,
+                audio_quality: AudioQuality::Medium,
+                extra_args: String::from("--extract-audio --audio-format mp3 --audio-quality 128K"),
+                ..Self::default()
+            },
+            Self {
+                name: String::from("Archive best MKV + subs"),
+                video: true,
+                filename_template: String::from("%(uploader)s - %(title)s [%(id)s]"),
+                extra_args: String::from(
+                    "--remux-video mkv --write-subs --sub-langs all --embed-subs",
+                ),
+                ..Self::default()
+            },
+        ]
+    }
+
+    /// Sets the preset's options, failing if its extra arguments can't be split.
+    pub fn apply(&self, options: &mut DownloadOptions) -> Result<(), String> {
+        options.video = self.video;
+        options.video_quality = self.video_quality;
+        options.video_codec = self.video_codec;
+        options.audio_quality = self.audio_quality;
+        options.audio_codec = self.audio_codec;
+        if !self.folder.trim().is_empty() {
+            options.output_dir = folders::expand(&self.folder);
+        }
+        options.filename_template = self.filename_template.trim().to_owned();
+        options.extra_args = split_args(&self.extra_args)?;
+        Ok(())
+    }
+}
+
+/// Splits arguments on whitespace like a shell would, keeping quoted parts together.
+/// A backslash escapes the next character, except within single quotes. Unbalanced
+/// quotes and a trailing backslash are rejected rather than guessed at.
+pub fn split_args(args: &str) -> Result<Vec<String>, String> {
+    let mut split = vec![];
+    let mut current: Option<String> = None;
+    let mut quote = None;
+    let mut chars = args.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None | Some('"'), '\\') => {
+                let escaped = chars
+                    .next()
+                    .ok_or("extra arguments end with a lone backslash")?;
+                current.get_or_insert_default().push(escaped);
+            }
+            (Some(_), c) => current.get_or_insert_default().push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.get_or_insert_default();
+            }
+            (None, c) if c.is_whitespace() => split.extend(current.take()),
+            (None, c) => current.get_or_insert_default().push(c),
+        }
+    }
+    if let Some(quote) = quote {
+        return Err(format!("extra arguments have an unclosed {quote} quote"));
+    }
+    split.extend(current);
+    Ok(split)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(args: &str) -> Vec<String> {
+        split_args(args).unwrap()
+    }
+
+    #[test]
+    fn arguments_split_on_whitespace() {
+        assert_eq!(
+            split("  --embed-subs\t--sub-langs  all \n"),
+            ["--embed-subs", "--sub-langs", "all"]
+        );
+        assert!(split("").is_empty());
+    }
+
+    #[test]
+    fn quotes_keep_arguments_together() {
+        assert_eq!(
+            split(r#"--output "%(title)s - %(id)s""#),
+            ["--output", "%(title)s - %(id)s"]
+        );
+        assert_eq!(split("--sub-langs 'en, pt'"), ["--sub-langs", "en, pt"]);
+        assert_eq!(split(r#"a"b c"d 'it"s'"#), ["ab cd", "it\"s"]);
+        // An empty quoted argument is still an argument
+        assert_eq!(split("--referer ''"), ["--referer", ""]);
+    }
+
+    #[test]
+    fn backslashes_escape_outside_single_quotes() {
+        assert_eq!(split(r"My\ Videos"), ["My Videos"]);
+        assert_eq!(split(r#""say \"hi\"""#), [r#"say "hi""#]);
+        assert_eq!(split(r"'C:\Videos'"), [r"C:\Videos"]);
+    }
+
+    #[test]
+    fn unbalanced_input_is_rejected() {
+        assert!(split_args(r#"--output "%(title)s"#).is_err());
+        assert!(split_args("--sub-langs 'en").is_err());
+        assert!(split_args(r"--output title\").is_err());
+    }
+
+    #[test]
+    fn builtin_presets_apply() {
+        for preset in Preset::builtin() {
+            let mut options = DownloadOptions::default();
+            assert_eq!(preset.apply(&mut options), Ok(()), "{}", preset.name);
+        }
+    }
+}
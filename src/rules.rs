@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Picking the output folder by where a download comes from.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{fl, folders};
+
+/// Download types a rule applies to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Kind {
+    #[default]
+    Any,
+    Video,
+    Audio,
+}
+
+impl Kind {
+    pub const ALL: [Self; 3] = [Self::Any, Self::Video, Self::Audio];
+
+    fn includes(self, video: bool) -> bool {
+        match self {
+            Kind::Any => true,
+            Kind::Video => video,
+            Kind::Audio => !video,
+        }
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::Any => write!(f, "{}", fl!("rule-any-type")),
+            Kind::Video => write!(f, "{}", fl!("video")),
+            Kind::Audio => write!(f, "{}", fl!("audio")),
+        }
+    }
+}
+
+/// Metadata field a rule matches on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Field {
+    #[default]
+    Domain,
+    Uploader,
+    Playlist,
+}
+
+impl Field {
+    pub const ALL: [Self; 3] = [Self::Domain, Self::Uploader, Self::Playlist];
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Field::Domain => write!(f, "{}", fl!("rule-domain")),
+            Field::Uploader => write!(f, "{}", fl!("rule-uploader")),
+            Field::Playlist => write!(f, "{}", fl!("rule-playlist")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderRule {
+    pub kind: Kind,
+    pub field: Field,
+    /// Value to match, ignoring case. Empty matches every item that has the field,
+    /// e.g. every playlist.
+    pub pattern: String,
+    /// Folder to save to, relative to the download type's folder unless absolute.
+    /// `%(domain)s`, `%(uploader)s` and `%(playlist)s` are filled in from the item.
+    pub folder: String,
+}
+
+impl FolderRule {
+    pub fn matches(&self, video: bool, metadata: &Metadata) -> bool {
+        if !self.kind.includes(video) {
+            return false;
+        }
+        let pattern = self.pattern.trim().to_lowercase();
+        match self.field {
+            Field::Domain => {
+                let domain = metadata.domain.to_lowercase();
+                pattern.is_empty() || domain == pattern || domain.ends_with(&format!(".{pattern}"))
+            }
+            Field::Uploader => metadata
+                .uploader
+                .as_ref()
+                .is_some_and(|uploader| pattern.is_empty() || uploader.to_lowercase() == pattern),
+            Field::Playlist => metadata
+                .playlist
+                .as_ref()
+                .is_some_and(|playlist| pattern.is_empty() || playlist.to_lowercase() == pattern),
+        }
+    }
+
+    /// The folder with the item's fields filled in, `NA` standing for missing ones
+    /// as in yt-dlp's output templates.
+    fn folder(&self, metadata: &Metadata) -> PathBuf {
+        let folder = self
+            .folder
+            .replace("%(domain)s", &value(Some(&metadata.domain)))
+            .replace("%(uploader)s", &value(metadata.uploader.as_ref()))
+            .replace("%(playlist)s", &value(metadata.playlist.as_ref()));
+        folders::expand(&folder)
+    }
+}
+
+/// A field as a single folder name, so uploaders and playlists can't name a path
+/// outside the rule's folder.
+fn value(value: Option<&String>) -> String {
+    match value.map(|value| value.trim()) {
+        None | Some("") => String::from("NA"),
+        Some("." | "..") => String::from("_"),
+        Some(value) => value.replace('/', "_"),
+    }
+}
+
+/// What rules are matched against.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// Host of the URL.
+    pub domain: String,
+    pub uploader: Option<String>,
+    /// Title of the playlist the URL points into, if any.
+    pub playlist: Option<String>,
+}
+
+/// Folder picked by the first matching rule, or `base` when none match.
+pub fn folder(rules: &[FolderRule], video: bool, metadata: &Metadata, base: &Path) -> PathBuf {
+    rules
+        .iter()
+        .find(|rule| rule.matches(video, metadata))
+        .map_or_else(
+            || base.to_path_buf(),
+            |rule| base.join(rule.folder(metadata)),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            domain: String::from("www.youtube.com"),
+            uploader: Some(String::from("Some Channel")),
+            playlist: Some(String::from("Talks")),
+        }
+    }
+
+    fn rule(field: Field, pattern: &str) -> FolderRule {
+        FolderRule {
+            field,
+            pattern: pattern.to_owned(),
+            folder: String::from("Rule"),
+            ..FolderRule::default()
+        }
+    }
+
+    #[test]
+    fn domains_match_subdomains_ignoring_case() {
+        let metadata = metadata();
+        assert!(rule(Field::Domain, "www.youtube.com").matches(true, &metadata));
+        assert!(rule(Field::Domain, "YouTube.com").matches(true, &metadata));
+        assert!(!rule(Field::Domain, "tube.com").matches(true, &metadata));
+        assert!(!rule(Field::Domain, "vimeo.com").matches(true, &metadata));
+    }
+
+    #[test]
+    fn uploaders_and_playlists_match_whole_values_ignoring_case() {
+        let metadata = metadata();
+        assert!(rule(Field::Uploader, "some channel").matches(true, &metadata));
+        assert!(!rule(Field::Uploader, "Some").matches(true, &metadata));
+        assert!(rule(Field::Playlist, "TALKS").matches(true, &metadata));
+        assert!(!rule(Field::Playlist, "Talk").matches(true, &metadata));
+    }
+
+    #[test]
+    fn empty_patterns_match_items_with_the_field() {
+        let metadata = metadata();
+        let single = Metadata {
+            uploader: None,
+            playlist: None,
+            ..metadata.clone()
+        };
+        for field in Field::ALL {
+            assert!(rule(field, " ").matches(true, &metadata), "{field:?}");
+        }
+        assert!(rule(Field::Domain, "").matches(true, &single));
+        assert!(!rule(Field::Uploader, "").matches(true, &single));
+        assert!(!rule(Field::Playlist, "").matches(true, &single));
+    }
+
+    #[test]
+    fn kinds_pick_download_types() {
+        let metadata = metadata();
+        for (kind, video, audio) in [
+            (Kind::Any, true, true),
+            (Kind::Video, true, false),
+            (Kind::Audio, false, true),
+        ] {
+            let rule = FolderRule {
+                kind,
+                ..rule(Field::Domain, "")
+            };
+            assert_eq!(rule.matches(true, &metadata), video, "{kind:?}");
+            assert_eq!(rule.matches(false, &metadata), audio, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn first_match_picks_the_folder() {
+        let rules = [
+            rule(Field::Domain, "vimeo.com"),
+            FolderRule {
+                folder: String::from("%(uploader)s/%(playlist)s"),
+                ..rule(Field::Domain, "youtube.com")
+            },
+            rule(Field::Domain, ""),
+        ];
+        let base = Path::new("/videos");
+        assert_eq!(
+            folder(&rules, true, &metadata(), base),
+            Path::new("/videos/Some Channel/Talks")
+        );
+        assert_eq!(folder(&rules[..1], true, &metadata(), base), base);
+    }
+
+    #[test]
+    fn missing_fields_are_na() {
+        let rule = FolderRule {
+            folder: String::from("%(domain)s/%(playlist)s"),
+            ..rule(Field::Domain, "")
+        };
+        let metadata = Metadata {
+            playlist: Some(String::from(" ")),
+            ..metadata()
+        };
+        assert_eq!(rule.folder(&metadata), Path::new("www.youtube.com/NA"));
+        let metadata = Metadata {
+            playlist: None,
+            ..metadata
+        };
+        assert_eq!(rule.folder(&metadata), Path::new("www.youtube.com/NA"));
+    }
+
+    #[test]
+    fn fields_stay_inside_the_folder() {
+        let rule = FolderRule {
+            folder: String::from("%(uploader)s/%(playlist)s"),
+            ..rule(Field::Domain, "")
+        };
+        for (uploader, playlist, expected) in [
+            ("..", ".", "_/_"),
+            ("../..", "a/b", ".._../a_b"),
+            ("/etc", "..foo", "_etc/..foo"),
+        ] {
+            let metadata = Metadata {
+                uploader: Some(uploader.to_owned()),
+                playlist: Some(playlist.to_owned()),
+                ..metadata()
+            };
+            assert_eq!(rule.folder(&metadata), Path::new(expected));
+        }
+    }
+
+    #[test]
+    fn rule_folders_expand_home() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let rule = FolderRule {
+            folder: String::from("~/Podcasts/%(uploader)s"),
+            ..rule(Field::Domain, "")
+        };
+        assert_eq!(
+            folder(&[rule], false, &metadata(), Path::new("/music")),
+            Path::new(&home).join("Podcasts/Some Channel")
+        );
+    }
+}
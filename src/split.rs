@@ -0,0 +1,297 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Splitting a downloaded file into one file per chapter.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use yt_dlp::executor::Executor;
+
+use crate::clip::{Chapter, Clip};
+
+/// Used when no template is set, e.g. `Mix - 03 Song`.
+pub const DEFAULT_TEMPLATE: &str = "%(title)s - %(section_number)02d %(section_title)s";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Split {
+    pub enabled: bool,
+    /// File name template, with `%(title)s`, `%(section_title)s` and `%(section_number)d`
+    /// as in yt-dlp's `chapter:` output template.
+    pub template: String,
+    /// Whether to write an `.m3u` playlist of the chapter files.
+    pub playlist: bool,
+}
+
+impl Split {
+    fn template(&self) -> &str {
+        let template = self.template.trim();
+        if template.is_empty() {
+            DEFAULT_TEMPLATE
+        } else {
+            template
+        }
+    }
+}
+
+/// Fills the template fields, supporting the `s` and zero-padded `d` conversions.
+pub fn file_name(template: &str, title: &str, number: usize, chapter: &str) -> String {
+    let mut name = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("%(") {
+        name.push_str(&rest[..start]);
+        let field = &rest[start + 2..];
+        let parsed = field.split_once(')').and_then(|(key, spec)| {
+            let end = spec.find(['s', 'd'])?;
+            Some((key, &spec[..end], &spec[end + 1..]))
+        });
+        let Some((key, width, after)) = parsed else {
+            rest = &rest[start..];
+            break;
+        };
+        let width: usize = width.trim_start_matches('0').parse().unwrap_or_default();
+        let _ = match key {
+            "title" => write!(name, "{title}"),
+            "section_title" => write!(name, "{chapter}"),
+            "section_number" => write!(name, "{number:0width$}"),
+            _ => write!(name, "NA"),
+        };
+        rest = after;
+    }
+    name.push_str(rest);
+    name.replace('/', "_")
+}
+
+/// Chapters as they fall inside `clip`, relative to its start.
+pub fn within(chapters: &[Chapter], clip: &Clip) -> Vec<Chapter> {
+    chapters
+        .iter()
+        .filter_map(|chapter| {
+            let (start_time, end_time) = clip.relative(chapter.start_time, chapter.end_time)?;
+            Some(Chapter {
+                title: chapter.title.clone(),
+                start_time,
+                end_time,
+            })
+        })
+        .collect()
+}
+
+/// Picks a path next to `file` for each name, numbering repeats as in `Name (2)` so
+/// no chapter overwrites another chapter, the file being split or a file that `exists`.
+fn outputs(file: &Path, names: &[String], exists: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let extension = file
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut paths: Vec<PathBuf> = vec![];
+    for name in names {
+        let taken = |path: &Path| path == file || paths.iter().any(|p| p == path) || exists(path);
+        let mut path = dir.join(format!("{name}{extension}"));
+        let mut copy = 1;
+        while taken(&path) {
+            copy += 1;
+            path = dir.join(format!("{name} ({copy}){extension}"));
+        }
+        paths.push(path);
+    }
+    paths
+}
+
+/// Cuts `file` at the chapter boundaries, removing it once every chapter is written.
+/// Audio chapters are tagged as tracks of an album named after the file.
+pub async fn split(
+    ffmpeg: &Path,
+    file: &Path,
+    chapters: &[Chapter],
+    split: &Split,
+    audio: bool,
+) -> Result<Vec<PathBuf>, String> {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let title = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let names: Vec<String> = chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| file_name(split.template(), &title, i + 1, &chapter.title))
+        .collect();
+    let files = outputs(file, &names, Path::exists);
+    for ((i, chapter), out) in chapters.iter().enumerate().zip(&files) {
+        let number = i + 1;
+        // Never overwrite, the names were picked to be free
+        let mut args = vec![
+            String::from("-n"),
+            String::from("-i"),
+            file.to_string_lossy().into_owned(),
+            String::from("-ss"),
+            chapter.start_time.to_string(),
+            String::from("-to"),
+            chapter.end_time.to_string(),
+            String::from("-map"),
+            String::from("0"),
+            String::from("-c"),
+            String::from("copy"),
+            String::from("-metadata"),
+            format!("title={}", chapter.title),
+        ];
+        if audio {
+            args.extend([
+                String::from("-metadata"),
+                format!("track={number}/{}", chapters.len()),
+                String::from("-metadata"),
+                format!("album={title}"),
+            ]);
+        }
+        args.push(out.to_string_lossy().into_owned());
+        let executor = Executor {
+            executable_path: ffmpeg.to_path_buf(),
+            timeout: Duration::default(),
+            args,
+        };
+        executor
+            .execute()
+            .await
+            .map_err(|err| format!("failed to split out '{}': {err}", chapter.title))?;
+    }
+    tokio::fs::remove_file(file)
+        .await
+        .map_err(|err| format!("failed to remove {}: {err}", file.display()))?;
+    if split.playlist {
+        playlist(dir, &title, chapters, &files).await?;
+    }
+    Ok(files)
+}
+
+/// Writes an extended M3U playlist of the chapter files next to them.
+async fn playlist(
+    dir: &Path,
+    title: &str,
+    chapters: &[Chapter],
+    files: &[PathBuf],
+) -> Result<(), String> {
+    let mut m3u = String::from("#EXTM3U\n");
+    for (chapter, file) in chapters.iter().zip(files) {
+        let seconds = (chapter.end_time - chapter.start_time).round();
+        let name = file.file_name().unwrap_or_default().to_string_lossy();
+        let _ = writeln!(m3u, "#EXTINF:{seconds},{}\n{name}", chapter.title);
+    }
+    let file = dir.join(format!("{title}.m3u"));
+    tokio::fs::write(&file, m3u)
+        .await
+        .map_err(|err| format!("failed to write {}: {err}", file.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(title: &str, start_time: f64, end_time: f64) -> Chapter {
+        Chapter {
+            title: title.to_owned(),
+            start_time,
+            end_time,
+        }
+    }
+
+    #[test]
+    fn templates_fill_their_fields() {
+        assert_eq!(
+            file_name(DEFAULT_TEMPLATE, "Mix", 3, "Song"),
+            "Mix - 03 Song"
+        );
+        assert_eq!(
+            file_name("%(section_title)s by %(title)s", "Mix", 1, "Song"),
+            "Song by Mix"
+        );
+    }
+
+    #[test]
+    fn numbers_are_padded_to_the_given_width() {
+        assert_eq!(file_name("%(section_number)d", "", 7, ""), "7");
+        assert_eq!(file_name("%(section_number)03d", "", 7, ""), "007");
+        assert_eq!(file_name("%(section_number)2d", "", 7, ""), "07");
+        assert_eq!(file_name("%(section_number)02d", "", 123, ""), "123");
+    }
+
+    #[test]
+    fn unknown_fields_are_not_available() {
+        assert_eq!(
+            file_name("%(uploader)s - %(title)s", "Mix", 1, ""),
+            "NA - Mix"
+        );
+        // An unclosed field is kept as written
+        assert_eq!(file_name("%(title)s %(title", "Mix", 1, ""), "Mix %(title");
+    }
+
+    #[test]
+    fn slashes_cannot_make_folders() {
+        assert_eq!(
+            file_name("%(title)s/%(section_title)s", "A/B", 1, "C/D"),
+            "A_B_C_D"
+        );
+    }
+
+    #[test]
+    fn repeated_names_are_numbered() {
+        let file = Path::new("/videos/Mix.mp4");
+        let names = [
+            String::from("Intro"),
+            String::from("Intro"),
+            String::from("Intro"),
+        ];
+        assert_eq!(
+            outputs(file, &names, |_| false),
+            [
+                PathBuf::from("/videos/Intro.mp4"),
+                PathBuf::from("/videos/Intro (2).mp4"),
+                PathBuf::from("/videos/Intro (3).mp4"),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_split_file_and_existing_files_are_never_outputs() {
+        let file = Path::new("/videos/Mix.mp4");
+        let names = [String::from("Mix"), String::from("Outro")];
+        let exists = |path: &Path| path == Path::new("/videos/Outro.mp4");
+        assert_eq!(
+            outputs(file, &names, exists),
+            [
+                PathBuf::from("/videos/Mix (2).mp4"),
+                PathBuf::from("/videos/Outro (2).mp4"),
+            ]
+        );
+    }
+
+    #[test]
+    fn chapters_outside_the_clip_are_dropped() {
+        let chapters = [
+            chapter("Intro", 0.0, 10.0),
+            chapter("Song", 10.0, 70.0),
+            chapter("Outro", 70.0, 80.0),
+        ];
+        let clip = Clip {
+            start: Some(30),
+            end: Some(70),
+        };
+        assert_eq!(within(&chapters, &clip), [chapter("Song", 0.0, 40.0)]);
+        assert_eq!(within(&chapters, &Clip::default()), chapters);
+    }
+
+    #[test]
+    fn chapters_are_trimmed_to_the_clip() {
+        let chapters = [chapter("Intro", 0.0, 10.0), chapter("Song", 10.0, 70.0)];
+        let clip = Clip {
+            start: Some(5),
+            end: Some(40),
+        };
+        assert_eq!(
+            within(&chapters, &clip),
+            [chapter("Intro", 0.0, 5.0), chapter("Song", 5.0, 35.0)]
+        );
+    }
+}
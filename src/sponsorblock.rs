@@ -0,0 +1,551 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Removing or marking sponsored and other segments submitted to SponsorBlock.
+
+use std::fmt::{Display, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+use yt_dlp::executor::Executor;
+
+use crate::clip::{Chapter, Clip};
+use crate::fl;
+use crate::network::Network;
+
+pub const DEFAULT_API_URL: &str = "https://sponsor.ajay.app";
+
+/// Segment categories, named as in the SponsorBlock API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Category {
+    #[serde(rename = "sponsor")]
+    Sponsor,
+    #[serde(rename = "selfpromo")]
+    SelfPromo,
+    #[serde(rename = "interaction")]
+    Interaction,
+    #[serde(rename = "intro")]
+    Intro,
+    #[serde(rename = "outro")]
+    Outro,
+    #[serde(rename = "preview")]
+    Preview,
+    #[serde(rename = "filler")]
+    Filler,
+    #[serde(rename = "music_offtopic")]
+    MusicOfftopic,
+}
+
+impl Category {
+    pub const ALL: [Self; 8] = [
+        Self::Sponsor,
+        Self::SelfPromo,
+        Self::Interaction,
+        Self::Intro,
+        Self::Outro,
+        Self::Preview,
+        Self::Filler,
+        Self::MusicOfftopic,
+    ];
+
+    fn api_name(self) -> &'static str {
+        match self {
+            Category::Sponsor => "sponsor",
+            Category::SelfPromo => "selfpromo",
+            Category::Interaction => "interaction",
+            Category::Intro => "intro",
+            Category::Outro => "outro",
+            Category::Preview => "preview",
+            Category::Filler => "filler",
+            Category::MusicOfftopic => "music_offtopic",
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Category::Sponsor => write!(f, "{}", fl!("category-sponsor")),
+            Category::SelfPromo => write!(f, "{}", fl!("category-selfpromo")),
+            Category::Interaction => write!(f, "{}", fl!("category-interaction")),
+            Category::Intro => write!(f, "{}", fl!("category-intro")),
+            Category::Outro => write!(f, "{}", fl!("category-outro")),
+            Category::Preview => write!(f, "{}", fl!("category-preview")),
+            Category::Filler => write!(f, "{}", fl!("category-filler")),
+            Category::MusicOfftopic => write!(f, "{}", fl!("category-music-offtopic")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    #[default]
+    Keep,
+    /// Add a chapter for the segment.
+    Mark,
+    /// Cut the segment out.
+    Remove,
+}
+
+impl Action {
+    pub const ALL: [Self; 3] = [Self::Keep, Self::Mark, Self::Remove];
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Keep => write!(f, "{}", fl!("segment-keep")),
+            Action::Mark => write!(f, "{}", fl!("segment-mark")),
+            Action::Remove => write!(f, "{}", fl!("segment-remove")),
+        }
+    }
+}
+
+/// A submitted segment, in seconds.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Segment {
+    pub category: Category,
+    #[serde(rename = "segment")]
+    pub range: (f64, f64),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SponsorBlock {
+    /// API server, empty for the public one.
+    pub api_url: String,
+    pub remove: Vec<Category>,
+    pub mark: Vec<Category>,
+}
+
+impl SponsorBlock {
+    pub fn is_set(&self) -> bool {
+        !self.remove.is_empty() || !self.mark.is_empty()
+    }
+
+    pub fn action(&self, category: Category) -> Action {
+        if self.remove.contains(&category) {
+            Action::Remove
+        } else if self.mark.contains(&category) {
+            Action::Mark
+        } else {
+            Action::Keep
+        }
+    }
+
+    pub fn set_action(&mut self, category: Category, action: Action) {
+        self.remove.retain(|removed| *removed != category);
+        self.mark.retain(|marked| *marked != category);
+        match action {
+            Action::Keep => {}
+            Action::Mark => self.mark.push(category),
+            Action::Remove => self.remove.push(category),
+        }
+    }
+
+    fn api_url(&self) -> &str {
+        let url = self.api_url.trim().trim_end_matches('/');
+        if url.is_empty() { DEFAULT_API_URL } else { url }
+    }
+
+    /// Looks up the segments of a YouTube video, moved into `clip` if it's set.
+    /// Other sites aren't covered by SponsorBlock, so they aren't asked about.
+    pub async fn segments(
+        &self,
+        network: &Network,
+        url: &str,
+        video_id: &str,
+        clip: &Clip,
+    ) -> Result<Vec<Segment>, String> {
+        let youtube = Url::parse(url).ok().is_some_and(|url| {
+            url.host_str().is_some_and(|host| {
+                ["youtube.com", "youtu.be"]
+                    .iter()
+                    .any(|domain| host == *domain || host.ends_with(&format!(".{domain}")))
+            })
+        });
+        if !youtube || !self.is_set() {
+            return Ok(vec![]);
+        }
+        let categories: Vec<&str> = self
+            .remove
+            .iter()
+            .chain(&self.mark)
+            .map(|category| category.api_name())
+            .collect();
+        let categories = format!("[\"{}\"]", categories.join("\",\""));
+        let response = network
+            .client()?
+            .get(format!("{}/api/skipSegments", self.api_url()))
+            .query(&[("videoID", video_id), ("categories", categories.as_str())])
+            .send()
+            .await
+            .map_err(|err| format!("failed to reach SponsorBlock: {err}"))?;
+        // Videos without segments are reported as not found
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(vec![]);
+        }
+        let body = response
+            .error_for_status()
+            .map_err(|err| format!("failed to get segments: {err}"))?
+            .text()
+            .await
+            .map_err(|err| format!("failed to get segments: {err}"))?;
+        let segments: Vec<Segment> =
+            serde_json::from_str(&body).map_err(|err| format!("failed to read segments: {err}"))?;
+        Ok(segments
+            .into_iter()
+            .filter_map(|segment| {
+                let range = clip.relative(segment.range.0, segment.range.1)?;
+                Some(Segment { range, ..segment })
+            })
+            .collect())
+    }
+
+    /// Segments to cut out, sorted and with overlaps merged.
+    fn removed(&self, segments: &[Segment]) -> Vec<(f64, f64)> {
+        let mut ranges: Vec<(f64, f64)> = segments
+            .iter()
+            .filter(|segment| self.action(segment.category) == Action::Remove)
+            .map(|segment| segment.range)
+            .collect();
+        ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut merged: Vec<(f64, f64)> = vec![];
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged
+    }
+
+    /// Moves chapters to where they end up once segments are removed, dropping
+    /// those removed entirely.
+    pub fn remap(&self, chapters: &[Chapter], segments: &[Segment]) -> Vec<Chapter> {
+        let removed = self.removed(segments);
+        chapters
+            .iter()
+            .map(|chapter| Chapter {
+                title: chapter.title.clone(),
+                start_time: remap(chapter.start_time, &removed),
+                end_time: remap(chapter.end_time, &removed),
+            })
+            .filter(|chapter| chapter.end_time > chapter.start_time)
+            .collect()
+    }
+
+    /// The item's `chapters` once segments are removed, with marked segments laid
+    /// over them as chapters of their own, as yt-dlp's `--sponsorblock-mark` does.
+    /// Chapters overlapping a marked segment are cut around it.
+    pub fn chapters(&self, chapters: &[Chapter], segments: &[Segment]) -> Vec<Chapter> {
+        let marked: Vec<Chapter> = segments
+            .iter()
+            .filter(|segment| self.action(segment.category) == Action::Mark)
+            .map(|segment| Chapter {
+                title: segment.category.to_string(),
+                start_time: segment.range.0,
+                end_time: segment.range.1,
+            })
+            .collect();
+        let mut marked = self.remap(&marked, segments);
+        marked.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+        // Overlapping segments start where the previous one ends
+        let mut position = 0.0_f64;
+        for chapter in &mut marked {
+            chapter.start_time = chapter.start_time.max(position);
+            position = position.max(chapter.end_time);
+        }
+        marked.retain(|chapter| chapter.end_time > chapter.start_time);
+
+        let mut merged = marked.clone();
+        for chapter in self.remap(chapters, segments) {
+            let mut start = chapter.start_time;
+            for segment in &marked {
+                if segment.end_time <= start || segment.start_time >= chapter.end_time {
+                    continue;
+                }
+                if segment.start_time > start {
+                    merged.push(Chapter {
+                        start_time: start,
+                        end_time: segment.start_time,
+                        ..chapter.clone()
+                    });
+                }
+                start = segment.end_time;
+            }
+            if chapter.end_time > start {
+                merged.push(Chapter {
+                    start_time: start,
+                    ..chapter
+                });
+            }
+        }
+        merged.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+        merged
+    }
+
+    /// Cuts out and marks segments in `file` with ffmpeg, replacing it. The file's
+    /// `chapters` are kept, moved to match.
+    pub async fn apply(
+        &self,
+        ffmpeg: &Path,
+        file: &Path,
+        chapters: &[Chapter],
+        segments: &[Segment],
+    ) -> Result<(), String> {
+        let removed = self.removed(segments);
+        let marked = segments
+            .iter()
+            .any(|segment| self.action(segment.category) == Action::Mark);
+        if removed.is_empty() && !marked {
+            return Ok(());
+        }
+        let chapters = self.chapters(chapters, segments);
+
+        let dir = file.parent().unwrap_or(Path::new("."));
+        let temp = |suffix: &str| {
+            tempfile::Builder::new()
+                .prefix(".yt-dlp")
+                .suffix(suffix)
+                .tempfile_in(dir)
+                .map(tempfile::NamedTempFile::into_temp_path)
+                .map_err(|err| format!("failed to create temp file: {err}"))
+        };
+        let extension = file
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .unwrap_or_default();
+        let out = temp(&extension)?;
+        let mut args = vec![String::from("-y")];
+
+        // Kept parts are joined with the concat demuxer, which seeks without re-encoding
+        let list = temp(".txt")?;
+        let quoted = file.to_string_lossy().replace('\'', "'\\''");
+        let mut concat = String::from("ffconcat version 1.0\n");
+        let mut position = 0.0;
+        for (start, end) in removed.iter().copied().chain([(f64::INFINITY, 0.0)]) {
+            if start > position {
+                let _ = writeln!(concat, "file '{quoted}'\ninpoint {position}");
+                if start.is_finite() {
+                    let _ = writeln!(concat, "outpoint {start}");
+                }
+            }
+            position = end;
+        }
+        tokio::fs::write(&list, concat)
+            .await
+            .map_err(|err| format!("failed to write {}: {err}", list.display()))?;
+        args.extend(yt_dlp::utils::to_owned(vec![
+            "-f", "concat", "-safe", "0", "-i",
+        ]));
+        args.push(list.to_string_lossy().into_owned());
+
+        // The joined parts would keep the chapters at their old times, so they're
+        // replaced with the moved ones
+        let metadata = temp(".txt")?;
+        if !chapters.is_empty() {
+            let mut contents = String::from(";FFMETADATA1\n");
+            for chapter in &chapters {
+                let _ = write!(
+                    contents,
+                    "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+                    (chapter.start_time * 1000.0) as u64,
+                    (chapter.end_time * 1000.0) as u64,
+                    escape(&chapter.title),
+                );
+            }
+            tokio::fs::write(&metadata, contents)
+                .await
+                .map_err(|err| format!("failed to write {}: {err}", metadata.display()))?;
+            args.extend([
+                String::from("-i"),
+                metadata.to_string_lossy().into_owned(),
+                String::from("-map_chapters"),
+                String::from("1"),
+            ]);
+        }
+        args.extend(yt_dlp::utils::to_owned(vec!["-map", "0", "-c", "copy"]));
+        args.push(out.to_string_lossy().into_owned());
+
+        let executor = Executor {
+            executable_path: ffmpeg.to_path_buf(),
+            timeout: Duration::default(),
+            args,
+        };
+        executor
+            .execute()
+            .await
+            .map_err(|err| format!("failed to edit {}: {err}", file.display()))?;
+        out.persist(file)
+            .map_err(|err| format!("failed to replace {}: {err}", file.display()))
+    }
+}
+
+/// Where `time` ends up once the `removed` ranges are cut out.
+fn remap(time: f64, removed: &[(f64, f64)]) -> f64 {
+    let cut: f64 = removed
+        .iter()
+        .map(|(start, end)| time.clamp(*start, *end) - start)
+        .sum();
+    time - cut
+}
+
+/// Escapes the characters with a special meaning in ffmpeg metadata files.
+fn escape(value: &str) -> String {
+    value.chars().fold(String::new(), |mut escaped, c| {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        escaped
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::testing::Server;
+
+    const VIDEO: &str = "https://www.youtube.com/watch?v=abc";
+
+    const SEGMENTS: &str = r#"[
+        {"category": "sponsor", "actionType": "skip", "segment": [10.0, 20.0], "UUID": "a"},
+        {"category": "intro", "actionType": "skip", "segment": [15.0, 25.0], "UUID": "b"},
+        {"category": "selfpromo", "actionType": "skip", "segment": [60.0, 70.0], "UUID": "c"}
+    ]"#;
+
+    fn sponsorblock(server: &Server) -> SponsorBlock {
+        SponsorBlock {
+            api_url: format!("{}/", server.url),
+            remove: vec![Category::Sponsor, Category::Intro],
+            mark: vec![Category::SelfPromo],
+        }
+    }
+
+    fn chapter(title: &str, start_time: f64, end_time: f64) -> Chapter {
+        Chapter {
+            title: title.to_owned(),
+            start_time,
+            end_time,
+        }
+    }
+
+    async fn segments(server: &Server, url: &str, clip: &Clip) -> Vec<Segment> {
+        sponsorblock(server)
+            .segments(&Network::default(), url, "abc", clip)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn segments_are_looked_up_by_video_and_category() {
+        let server = Server::start(&[("/api/skipSegments", 200, SEGMENTS.as_bytes())]).await;
+        let segments = segments(&server, VIDEO, &Clip::default()).await;
+
+        assert_eq!(
+            segments,
+            [
+                Segment {
+                    category: Category::Sponsor,
+                    range: (10.0, 20.0)
+                },
+                Segment {
+                    category: Category::Intro,
+                    range: (15.0, 25.0)
+                },
+                Segment {
+                    category: Category::SelfPromo,
+                    range: (60.0, 70.0)
+                },
+            ]
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let url = Url::parse(&format!("{}{}", server.url, requests[0].target)).unwrap();
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(query["videoID"], "abc");
+        assert_eq!(query["categories"], r#"["sponsor","intro","selfpromo"]"#);
+    }
+
+    #[tokio::test]
+    async fn segments_move_into_clips() {
+        let server = Server::start(&[("/api/skipSegments", 200, SEGMENTS.as_bytes())]).await;
+        let clip = Clip {
+            start: Some(18),
+            end: Some(50),
+        };
+        let ranges: Vec<(f64, f64)> = segments(&server, VIDEO, &clip)
+            .await
+            .iter()
+            .map(|segment| segment.range)
+            .collect();
+        assert_eq!(ranges, [(0.0, 2.0), (0.0, 7.0)]);
+    }
+
+    #[tokio::test]
+    async fn only_youtube_videos_with_segments_have_any() {
+        let server = Server::start(&[]).await;
+        assert!(segments(&server, VIDEO, &Clip::default()).await.is_empty());
+        assert!(
+            segments(&server, "https://vimeo.com/1", &Clip::default())
+                .await
+                .is_empty()
+        );
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn removed_segments_are_merged_and_chapters_moved() {
+        let server = Server::start(&[("/api/skipSegments", 200, SEGMENTS.as_bytes())]).await;
+        let sponsorblock = sponsorblock(&server);
+        let segments = segments(&server, VIDEO, &Clip::default()).await;
+        assert_eq!(sponsorblock.removed(&segments), [(10.0, 25.0)]);
+
+        let chapters = [
+            chapter("Intro", 0.0, 10.0),
+            chapter("Sponsor read", 12.0, 24.0),
+            chapter("Main", 24.0, 50.0),
+            chapter("Ad", 50.0, 75.0),
+            chapter("End", 75.0, 100.0),
+        ];
+        assert_eq!(
+            sponsorblock.remap(&chapters, &segments),
+            [
+                chapter("Intro", 0.0, 10.0),
+                chapter("Main", 10.0, 35.0),
+                chapter("Ad", 35.0, 60.0),
+                chapter("End", 60.0, 85.0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn marked_segments_are_laid_over_chapters() {
+        let server = Server::start(&[("/api/skipSegments", 200, SEGMENTS.as_bytes())]).await;
+        let sponsorblock = sponsorblock(&server);
+        let segments = segments(&server, VIDEO, &Clip::default()).await;
+        let selfpromo = Category::SelfPromo.to_string();
+
+        let chapters = [
+            chapter("Intro", 0.0, 25.0),
+            chapter("Main", 25.0, 65.0),
+            chapter("End", 65.0, 100.0),
+        ];
+        assert_eq!(
+            sponsorblock.chapters(&chapters, &segments),
+            [
+                chapter("Intro", 0.0, 10.0),
+                chapter("Main", 10.0, 45.0),
+                chapter(&selfpromo, 45.0, 55.0),
+                chapter("End", 55.0, 85.0),
+            ]
+        );
+        assert_eq!(
+            sponsorblock.chapters(&[], &segments),
+            [chapter(&selfpromo, 45.0, 55.0)]
+        );
+    }
+}
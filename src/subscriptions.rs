@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Channels and playlists that are polled for new items.
+
+use serde::{Deserialize, Serialize};
+
+use crate::archive::Archive;
+use crate::fetcher::{self, Binaries};
+
+/// How many of the newest items are looked at on every poll.
+pub const RECENT_ITEMS: usize = 50;
+
+/// A followed channel or playlist.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Feed {
+    pub url: String,
+    /// Preset to download new items with, empty for the popup's selection.
+    pub preset: String,
+    /// Output folder, empty for the preset's or the popup's.
+    pub folder: String,
+    /// Archive keys of the items that were there when subscribing, which aren't downloaded.
+    pub baseline: Vec<String>,
+}
+
+/// An item of a feed.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub key: String,
+    pub url: String,
+}
+
+impl Feed {
+    /// Subscribes to `url`, leaving its current items out unless `existing` is set.
+    pub async fn subscribe(
+        binaries: &Binaries,
+        args: Vec<String>,
+        url: String,
+        preset: String,
+        folder: String,
+        existing: bool,
+    ) -> Result<Self, String> {
+        let entries = fetcher::playlist_entries(binaries, args, &url, RECENT_ITEMS).await?;
+        let baseline = if existing {
+            vec![]
+        } else {
+            entries.into_iter().map(|entry| entry.key).collect()
+        };
+        Ok(Self {
+            url,
+            preset,
+            folder,
+            baseline,
+        })
+    }
+
+    /// URLs of recent items that are neither in the baseline nor in the archive.
+    pub async fn new_items(
+        &self,
+        binaries: &Binaries,
+        args: Vec<String>,
+        archive: &Archive,
+    ) -> Result<Vec<String>, String> {
+        let entries = fetcher::playlist_entries(binaries, args, &self.url, RECENT_ITEMS).await?;
+        let mut urls = vec![];
+        for entry in entries {
+            if !self.baseline.contains(&entry.key) && !archive.contains(&entry.key).await {
+                urls.push(entry.url);
+            }
+        }
+        Ok(urls)
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! A local HTTP server answering fixed routes, standing in for remote services
+//! in tests. It only uses tokio, so the integration tests can include it too.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request the server received.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// Path and query, e.g. `/api/skipSegments?videoID=1`.
+    pub target: String,
+    /// Headers, with lowercase names.
+    pub headers: HashMap<String, String>,
+}
+
+impl Request {
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+}
+
+type Routes = HashMap<String, (u16, Vec<u8>)>;
+
+pub struct Server {
+    /// Base URL, e.g. `http://127.0.0.1:4321`.
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Server {
+    /// Serves each route's status and body by path, ignoring the query. Other
+    /// paths are not found.
+    pub async fn start(routes: &[(&str, u16, &[u8])]) -> Self {
+        let routes: Arc<Routes> = Arc::new(
+            routes
+                .iter()
+                .map(|(path, status, body)| ((*path).to_owned(), (*status, body.to_vec())))
+                .collect(),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (routes, received) = (routes.clone(), received.clone());
+                tokio::spawn(async move { respond(stream, &routes, &received).await });
+            }
+        });
+        Self { url, requests }
+    }
+
+    /// Requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    routes: &Routes,
+    received: &Mutex<Vec<Request>>,
+) -> Option<()> {
+    let mut head = vec![];
+    let mut buf = [0; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    let head = String::from_utf8_lossy(&head).into_owned();
+    let mut lines = head.lines();
+    let mut start = lines.next()?.split(' ');
+    let (method, target) = (start.next()?.to_owned(), start.next()?.to_owned());
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_owned()))
+        .collect();
+    let request = Request {
+        method,
+        target,
+        headers,
+    };
+
+    let (status, body) = routes
+        .get(request.path())
+        .cloned()
+        .unwrap_or_else(|| (404, b"not found".to_vec()));
+    let head_only = request.method == "HEAD";
+    // Recorded before answering, so it's there once the client has the response
+    received.lock().unwrap().push(request);
+    let head = format!(
+        "HTTP/1.1 {status} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.ok()?;
+    if !head_only {
+        stream.write_all(&body).await.ok()?;
+    }
+    stream.shutdown().await.ok()
+}
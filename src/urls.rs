@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Checking and cleaning up URLs before they're queued.
+
+use std::fmt::Display;
+
+use url::Url;
+
+use crate::fl;
+use crate::network::Network;
+
+/// Query parameters that only track where a link was shared from.
+const TRACKING: [&str; 12] = [
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "ref_src",
+    "ref_url", "_hsenc", "_hsmi",
+];
+
+/// Tracking parameters specific to YouTube share links.
+const YOUTUBE_TRACKING: [&str; 3] = ["si", "feature", "pp"];
+
+/// Link shorteners, expanded by following their redirect.
+const SHORTENERS: [&str; 10] = [
+    "bit.ly",
+    "t.co",
+    "tinyurl.com",
+    "goo.gl",
+    "ow.ly",
+    "buff.ly",
+    "is.gd",
+    "rb.gy",
+    "lnkd.in",
+    "dlvr.it",
+];
+
+/// Why the input can't be downloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invalid {
+    Empty,
+    NotUrl,
+    Scheme,
+}
+
+impl Display for Invalid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Invalid::Empty => write!(f, "{}", fl!("url-empty")),
+            Invalid::NotUrl => write!(f, "{}", fl!("url-invalid")),
+            Invalid::Scheme => write!(f, "{}", fl!("url-scheme")),
+        }
+    }
+}
+
+fn is_host(url: &Url, domain: &str) -> bool {
+    url.host_str()
+        .is_some_and(|host| host == domain || host.ends_with(&format!(".{domain}")))
+}
+
+/// Parses a web URL, assuming https when the scheme is left out.
+pub fn parse(input: &str) -> Result<Url, Invalid> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(Invalid::Empty);
+    }
+    let url = match Url::parse(input) {
+        Ok(url) => url,
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            Url::parse(&format!("https://{input}")).map_err(|_| Invalid::NotUrl)?
+        }
+        Err(_) => return Err(Invalid::NotUrl),
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(Invalid::Scheme);
+    }
+    // Hosts need a dot, so typos like `youtube` aren't taken for a host
+    if !url.host_str().is_some_and(|host| host.contains('.')) {
+        return Err(Invalid::NotUrl);
+    }
+    Ok(url)
+}
+
+/// Drops tracking parameters and rewrites YouTube short links to full ones.
+pub fn clean(mut url: Url) -> Url {
+    let youtube = is_host(&url, "youtube.com") || is_host(&url, "youtu.be");
+    if is_host(&url, "youtu.be") {
+        let id = url.path().trim_start_matches('/').to_owned();
+        if !id.is_empty() {
+            let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+            url = Url::parse("https://www.youtube.com/watch").unwrap();
+            url.query_pairs_mut()
+                .append_pair("v", &id)
+                .extend_pairs(query);
+        }
+    }
+    let query: Vec<(String, String)> = url
+        .query_pairs()
+        .into_owned()
+        .filter(|(key, _)| {
+            !key.starts_with("utm_")
+                && !TRACKING.contains(&key.as_str())
+                && !(youtube && YOUTUBE_TRACKING.contains(&key.as_str()))
+        })
+        .collect();
+    if query.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(query);
+    }
+    url
+}
+
+/// Checks and cleans up typed input, without network access.
+pub fn normalize(input: &str) -> Result<String, Invalid> {
+    parse(input).map(|url| clean(url).to_string())
+}
+
+/// Follows link shorteners to where they point, leaving other URLs as they are.
+pub async fn expand(network: &Network, url: &str) -> String {
+    let Ok(parsed) = Url::parse(url) else {
+        return url.to_owned();
+    };
+    if !SHORTENERS.iter().any(|domain| is_host(&parsed, domain)) {
+        return url.to_owned();
+    }
+    let Ok(client) = network.client() else {
+        return url.to_owned();
+    };
+    match client.head(url).send().await {
+        Ok(response) => clean(response.url().clone()).to_string(),
+        Err(_) => url.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cleaned(url: &str) -> String {
+        clean(Url::parse(url).unwrap()).to_string()
+    }
+
+    #[test]
+    fn schemes_default_to_https() {
+        assert_eq!(normalize("example.com/a").unwrap(), "https://example.com/a");
+        assert_eq!(
+            normalize("  http://example.com/a  ").unwrap(),
+            "http://example.com/a"
+        );
+    }
+
+    #[test]
+    fn non_web_input_is_rejected() {
+        assert_eq!(parse(""), Err(Invalid::Empty));
+        assert_eq!(parse(" \t"), Err(Invalid::Empty));
+        assert_eq!(parse("not a url"), Err(Invalid::NotUrl));
+        assert_eq!(parse("ftp://example.com/file"), Err(Invalid::Scheme));
+        assert_eq!(parse("mailto:someone@example.com"), Err(Invalid::Scheme));
+    }
+
+    #[test]
+    fn hosts_need_a_dot() {
+        assert_eq!(parse("youtube"), Err(Invalid::NotUrl));
+        assert_eq!(parse("https://localhost/video"), Err(Invalid::NotUrl));
+        assert!(parse("127.0.0.1/video").is_ok());
+    }
+
+    #[test]
+    fn tracking_parameters_are_dropped() {
+        assert_eq!(
+            cleaned("https://example.com/a?utm_source=feed&id=1&fbclid=2&gclid=3"),
+            "https://example.com/a?id=1"
+        );
+        assert_eq!(
+            cleaned("https://example.com/a?utm_medium=social"),
+            "https://example.com/a"
+        );
+    }
+
+    #[test]
+    fn youtube_share_parameters_are_dropped_only_on_youtube() {
+        assert_eq!(
+            cleaned("https://www.youtube.com/watch?v=abc&si=x&feature=share&t=10"),
+            "https://www.youtube.com/watch?v=abc&t=10"
+        );
+        assert_eq!(
+            cleaned("https://m.youtube.com/watch?v=abc&pp=x"),
+            "https://m.youtube.com/watch?v=abc"
+        );
+        assert_eq!(
+            cleaned("https://example.com/?si=1&feature=a"),
+            "https://example.com/?si=1&feature=a"
+        );
+        assert_eq!(
+            cleaned("https://notyoutube.com/?si=1"),
+            "https://notyoutube.com/?si=1"
+        );
+    }
+
+    #[test]
+    fn short_links_are_rewritten() {
+        assert_eq!(
+            cleaned("https://youtu.be/abc?t=42&si=x"),
+            "https://www.youtube.com/watch?v=abc&t=42"
+        );
+        // Without a video there's nothing to rewrite to
+        assert_eq!(cleaned("https://youtu.be/"), "https://youtu.be/");
+    }
+
+    #[tokio::test]
+    async fn only_shorteners_are_expanded() {
+        let url = "https://example.com/a?id=1";
+        assert_eq!(expand(&Network::default(), url).await, url);
+        assert_eq!(expand(&Network::default(), "not a url").await, "not a url");
+    }
+}
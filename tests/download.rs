@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Runs the `download` subcommand end to end, with stub yt-dlp and ffmpeg
+//! executables and a local server standing in for the media hosts.
+
+#[allow(dead_code)]
+#[path = "../src/testing.rs"]
+mod testing;
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use tempfile::TempDir;
+
+use testing::Server;
+
+const PLAYLIST: &[u8] = include_bytes!("fixtures/video.m3u8");
+/// Name yt-dlp gives the fixture, without the extension.
+const TITLE: &str = "Fixture clip [fixture]";
+
+fn fixture(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(path)
+}
+
+/// A home folder for one run, and the server the fixture's formats point at.
+struct Home {
+    dir: TempDir,
+    server: Server,
+}
+
+impl Home {
+    async fn new() -> Self {
+        let server = Server::start(&[
+            ("/hls/video.m3u8", 200, PLAYLIST),
+            ("/hls/segment0.ts", 200, b"segment0"),
+            ("/hls/segment1.ts", 200, b"segment1"),
+            ("/video/low.mp4", 200, b"low"),
+            ("/audio/140.m4a", 200, b"aac"),
+            ("/audio/251.webm", 200, b"opus"),
+        ])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let info = std::fs::read_to_string(fixture("video.json")).unwrap();
+        let info = info.replace("{server}", &server.url);
+        std::fs::write(dir.path().join("info.json"), info).unwrap();
+        let home = Self { dir, server };
+        // The stubs are set up as custom binaries, as a user would set their own
+        home.set("binary_source", "Custom");
+        for (key, stub) in [
+            ("custom_yt_dlp", "bin/yt-dlp"),
+            ("custom_ffmpeg", "bin/ffmpeg"),
+        ] {
+            home.set(key, &format!("{:?}", fixture(stub).display().to_string()));
+        }
+        home
+    }
+
+    fn output(&self) -> PathBuf {
+        self.dir.path().join("Downloads")
+    }
+
+    /// Saves a setting where the applet's config is read from, as RON.
+    fn set(&self, key: &str, value: &str) {
+        let config = self
+            .dir
+            .path()
+            .join("config/cosmic/dev.DBrox.CosmicYtdlp/v1");
+        std::fs::create_dir_all(&config).unwrap();
+        std::fs::write(config.join(key), value).unwrap();
+    }
+
+    /// Downloads the fixture into [`Self::output`], failing the test if it doesn't work.
+    async fn download(&self, args: &[&str]) {
+        let home = self.dir.path();
+        let Output { status, stderr, .. } =
+            tokio::process::Command::new(env!("CARGO_BIN_EXE_cosmic-ext-applet-yt-dlp"))
+                .arg("download")
+                .args(args)
+                .arg("--output")
+                .arg(self.output())
+                .arg(format!("{}/watch?v=fixture", self.server.url))
+                .env("HOME", home)
+                .env("XDG_CONFIG_HOME", home.join("config"))
+                .env("XDG_DATA_HOME", home.join("data"))
+                .env("STUB_INFO", home.join("info.json"))
+                .env("STUB_LOG", home.join("yt-dlp.log"))
+                .output()
+                .await
+                .unwrap();
+        let stderr = String::from_utf8_lossy(&stderr);
+        assert!(status.success(), "{status}: {stderr}");
+    }
+
+    /// Paths requested from the server, in order.
+    fn requested(&self) -> Vec<String> {
+        self.server
+            .requests()
+            .iter()
+            .map(|request| request.path().to_owned())
+            .collect()
+    }
+
+    /// Files left in the output folder, with their contents.
+    fn files(&self) -> Vec<(String, Vec<u8>)> {
+        let mut files: Vec<(String, Vec<u8>)> = std::fs::read_dir(self.output())
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let name = entry.file_name().to_string_lossy().into_owned();
+                (name, std::fs::read(entry.path()).unwrap())
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Arguments yt-dlp was run with, one per line.
+    fn ytdlp_args(&self) -> String {
+        std::fs::read_to_string(self.dir.path().join("yt-dlp.log")).unwrap()
+    }
+}
+
+#[tokio::test]
+async fn hls_formats_are_joined_from_their_playlist() {
+    let home = Home::new().await;
+    home.download(&[]).await;
+
+    let requested = home.requested();
+    assert!(requested.contains(&String::from("/hls/video.m3u8")));
+    // Segments come from next to the playlist, not the page
+    assert!(requested.contains(&String::from("/hls/segment0.ts")));
+    assert!(requested.contains(&String::from("/hls/segment1.ts")));
+    assert!(!requested.contains(&String::from("/video/low.mp4")));
+    assert_eq!(
+        home.files(),
+        [(format!("{TITLE}.mp4"), b"segment0segment1".to_vec())]
+    );
+}
+
+#[tokio::test]
+async fn clips_only_fetch_the_segments_they_cover() {
+    let home = Home::new().await;
+    home.download(&["--start", "5"]).await;
+
+    let requested = home.requested();
+    assert!(!requested.contains(&String::from("/hls/segment0.ts")));
+    assert!(requested.contains(&String::from("/hls/segment1.ts")));
+    assert_eq!(
+        home.files(),
+        [(format!("{TITLE}.mp4"), b"segment1".to_vec())]
+    );
+}
+
+#[tokio::test]
+async fn video_quality_picks_the_format() {
+    let home = Home::new().await;
+    // A rate limit has the format fetched directly rather than through yt-dlp-rs
+    home.download(&["--quality", "lowest", "--limit-rate", "10M"])
+        .await;
+
+    let requested = home.requested();
+    assert!(requested.contains(&String::from("/video/low.mp4")));
+    assert!(!requested.iter().any(|path| path.starts_with("/hls/")));
+    assert_eq!(home.files(), [(format!("{TITLE}.mp4"), b"low".to_vec())]);
+}
+
+#[tokio::test]
+async fn audio_downloads_the_best_audio_format() {
+    let home = Home::new().await;
+    home.download(&["--type", "audio", "--limit-rate", "10M"])
+        .await;
+
+    let requested = home.requested();
+    assert!(requested.contains(&String::from("/audio/251.webm")));
+    assert!(!requested.contains(&String::from("/audio/140.m4a")));
+    assert_eq!(home.files(), [(format!("{TITLE}.m4a"), b"opus".to_vec())]);
+}
+
+#[tokio::test]
+async fn presets_name_files_with_their_template() {
+    let home = Home::new().await;
+    home.set(
+        "presets",
+        r#"[(
+    name: "Named",
+    video: true,
+    video_quality: Lowest,
+    video_codec: Any,
+    audio_quality: Best,
+    audio_codec: Any,
+    folder: "",
+    filename_template: "%(uploader)s - %(title)s",
+    extra_args: "",
+)]"#,
+    );
+    home.download(&["--preset", "Named", "--limit-rate", "10M"])
+        .await;
+
+    // The template is rendered by yt-dlp from the metadata it already gave
+    let args = home.ytdlp_args();
+    assert!(args.contains("--load-info-json\n"));
+    assert!(args.contains("%(uploader)s - %(title)s\n"));
+    assert_eq!(
+        home.files(),
+        [(
+            String::from("Fixture Channel - Fixture clip.mp4"),
+            b"low".to_vec()
+        )]
+    );
+}